@@ -0,0 +1,104 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+// Enough to get past the share's first burst without turning the benchmark into a full copy
+const SAMPLE_MAX_FILES: usize = 20;
+const SAMPLE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+const SAMPLE_MAX_DEPTH: usize = 3;
+const READ_BUFFER_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct SourceBenchmark {
+    pub files_read: usize,
+    pub bytes_read: u64,
+    pub duration_ms: u64,
+    pub throughput_bytes_per_sec: u64,
+    pub avg_open_latency_ms: f64, // Open plus first read, i.e. what every small file costs
+    pub max_open_latency_ms: f64,
+}
+
+/// Reads a sample of the files already under `remote_path` (largest first) and reports how fast
+/// the share delivers them. Nothing is written to the source. Files the OS has cached recently
+/// will read faster than a cold copy would.
+#[tauri::command]
+pub async fn benchmark_source(remote_path: String) -> Result<SourceBenchmark, String> {
+    tauri::async_runtime::spawn_blocking(move || run_benchmark(Path::new(&remote_path)))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn run_benchmark(root: &Path) -> Result<SourceBenchmark, String> {
+    if !root.is_dir() {
+        return Err(format!("{} is not a reachable folder", root.display()));
+    }
+
+    let mut files = vec![];
+    collect_files(root, 0, &mut files);
+    if files.is_empty() {
+        return Err(format!("No readable files under {}", root.display()));
+    }
+    files.sort_by_key(|f| std::cmp::Reverse(f.1));
+
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    let mut latencies: Vec<Duration> = vec![];
+    let mut bytes_read = 0u64;
+    let started = Instant::now();
+
+    for (path, _) in files.into_iter().take(SAMPLE_MAX_FILES) {
+        if bytes_read >= SAMPLE_MAX_BYTES {
+            break;
+        }
+        let opened = Instant::now();
+        let mut file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                log::warn!("Benchmark skipped {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let mut first_read = true;
+        loop {
+            let n = file.read(&mut buffer).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            if first_read {
+                latencies.push(opened.elapsed());
+                first_read = false;
+            }
+            if n == 0 {
+                break;
+            }
+            bytes_read += n as u64;
+            if bytes_read >= SAMPLE_MAX_BYTES {
+                break;
+            }
+        }
+    }
+
+    if latencies.is_empty() {
+        return Err(format!("None of the files under {} could be opened", root.display()));
+    }
+
+    let elapsed = started.elapsed();
+    let latency_ms = |d: &Duration| d.as_secs_f64() * 1000.0;
+    Ok(SourceBenchmark {
+        files_read: latencies.len(),
+        bytes_read,
+        duration_ms: elapsed.as_millis() as u64,
+        throughput_bytes_per_sec: (bytes_read as f64 / elapsed.as_secs_f64().max(0.001)) as u64,
+        avg_open_latency_ms: latencies.iter().map(latency_ms).sum::<f64>() / latencies.len() as f64,
+        max_open_latency_ms: latencies.iter().map(latency_ms).fold(0.0, f64::max),
+    })
+}
+
+fn collect_files(dir: &Path, depth: usize, out: &mut Vec<(PathBuf, u64)>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else { continue };
+        if metadata.is_file() {
+            out.push((entry.path(), metadata.len()));
+        } else if metadata.is_dir() && depth < SAMPLE_MAX_DEPTH {
+            collect_files(&entry.path(), depth + 1, out);
+        }
+    }
+}
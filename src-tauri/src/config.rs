@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use tauri::Manager;
 
+use crate::logging::LogLevel;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DeployServer {
     pub id: String,
@@ -12,7 +15,41 @@ pub struct DeployServer {
     pub port: u16,
     pub user: String,
     pub password: String,
+    #[serde(default)]
+    pub auth_method: AuthMethod, // Configs saved before key auth existed are all password based
+    #[serde(default)]
+    pub private_key_path: Option<String>, // OpenSSH or PEM private key, e.g. ~/.ssh/id_ed25519
+    #[serde(default)]
+    pub passphrase: Option<String>, // Only if the private key is encrypted
+    // On disk only: password and passphrase are encrypted with the keyring key. Always false in memory.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub encrypted: bool,
     pub remote_path: String,
+    // Upload lands in <staging_path>/<folder> and is moved into remote_path once complete
+    #[serde(default)]
+    pub staging_path: Option<String>,
+    // Remote folder name instead of the local one: literal or ${folder}/${version}/${date} tokens
+    #[serde(default)]
+    pub remote_folder_name_template: String,
+    // Glob or extension (e.g. "tar.gz"): only upload the newest matching file
+    #[serde(default)]
+    pub deploy_single_artifact: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AuthMethod {
+    #[default]
+    Password,
+    PublicKey,
+}
+
+/// What happens to a build folder that is already at the destination.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SyncMode {
+    SkipExisting, // Leave the whole folder alone
+    #[default]
+    Incremental, // Copy files that are missing or whose size changed or source is newer
+    Overwrite, // Copy every file again
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -46,18 +83,101 @@ pub struct AppConfig {
     pub remote_paths: Vec<String>,
     #[serde(default)]
     pub target_versions: Vec<String>,
+    // Per-path version lists; paths without an entry fall back to target_versions
+    #[serde(default)]
+    pub path_versions: HashMap<String, Vec<String>>,
     
     pub local_path: String,
+    #[serde(default)]
+    pub additional_local_paths: Vec<String>, // Every folder copied to local_path is also copied here, e.g. a NAS
+    #[serde(default = "default_true")]
+    pub create_local_path_if_missing: bool, // Off = the destination (e.g. an external drive) must already exist
     pub interval_minutes: u64,
+    #[serde(default)]
+    pub scheduler_enabled: bool, // Backend scans every interval_minutes, also with the window closed
     pub time_ranges: Vec<String>, // "HH:mm-HH:mm"
     // New fields for filtering
     pub file_extensions: Vec<String>, // e.g. ["exe", "tar.gz"]
     pub filename_includes: Vec<String>, // e.g. ["UMS", "VMS"] - OR logic
+    #[serde(default = "default_true")]
+    pub skip_hidden_files: bool, // Dotfiles, hidden/system files and junk like Thumbs.db, desktop.ini
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>, // Globs on the path relative to the build folder, e.g. ["*.tmp", "node_modules/**"]
+    #[serde(default)]
+    pub pinned_folders: Vec<String>, // Exact folder names copied even outside the date window
+    #[serde(default = "default_max_age_days")]
+    pub max_age_days: u32, // Date window: today and this many days back, 0 = today only
+    #[serde(default = "default_versions_to_keep")]
+    pub versions_to_keep: usize, // Newest builds per version copied each scan, e.g. 3 to stage rollbacks
+    #[serde(default = "default_folder_patterns")]
+    pub folder_patterns: Vec<String>, // Tried in order; group 1/`datetime` and 2/`version`
+    #[serde(default)]
+    pub datetime_format: String, // chrono format of the datetime group, e.g. "%Y%m%d-%H%M"; empty = "%Y_%m_%d_%H_%M"
+    // Version given to folders that fit a pattern but capture no version. They then match a task
+    // targeting exactly this version and count as versioned for "newest_any"; empty keeps them unmatched.
+    #[serde(default)]
+    pub default_version: String,
+    #[serde(default = "default_empty_versions_behavior")]
+    pub empty_versions_behavior: String, // "nothing" or "newest_any" when a task has no target version
+    #[serde(default = "default_tie_breaker")]
+    pub tie_breaker: String, // "mtime" (then name) or "name" for same-minute candidates
+    #[serde(default = "default_copy_order")]
+    pub copy_order: String, // "walk", "name", "size_asc", "size_desc"
+    #[serde(default)]
+    pub force_overwrite_readonly: bool, // Clear read-only attribute on existing destination files
+    #[serde(default)]
+    pub max_bytes_per_sec: u64, // Copy/upload bandwidth cap, 0 = unlimited; adjustable live
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    #[serde(default)]
+    pub normalize_line_endings: Option<String>, // "lf" or "crlf"; binary files are left alone
+    #[serde(default)]
+    pub line_ending_extensions: Vec<String>, // e.g. ["conf", "sh", "properties"]
+    #[serde(default)]
+    pub use_reflink: bool, // Try a copy-on-write clone first, fall back to a byte copy
+    #[serde(default)]
+    pub detect_source_drift: bool, // Re-check each source file's size/mtime before and after copying it
+    #[serde(default)]
+    pub completion_sentinel: Option<String>, // e.g. "DONE"; folders without this file are not copied yet
+    #[serde(default)]
+    pub move_instead_of_copy: bool, // Rename on the same volume, else copy then delete the source
+    #[serde(default)]
+    pub sync_mode: SyncMode,
+    #[serde(default)]
+    pub follow_symlinks: bool, // Copy and upload what links point at; off skips them
+    #[serde(default)]
+    pub preserve_ownership: bool, // Unix only: copy source uid/gid to destination files when permitted
+    #[serde(default)]
+    pub post_copy_delay_ms: u64, // Pause between finishing a copy and deploying it
+    #[serde(default)]
+    pub per_file_timeout_secs: u64, // Give up on a file that makes no progress this long, 0 = wait forever
+    #[serde(default)]
+    pub fsync_before_deploy: bool, // Flush copied files to disk before deploying
+    #[serde(default = "default_staging_max_age_hours")]
+    pub staging_max_age_hours: u64, // Leftover *.partial/*.incoming dirs the tool created (marked) older than this are removed
+    #[serde(default)]
+    pub safe_mode: bool, // Read and copy locally only; overrides deploy and every delete/remote-command switch
+    #[serde(default)]
+    pub quiet_mode: bool, // Suppress copy-progress events for scripted runs
+    #[serde(default)]
+    pub redact_paths: bool, // Mask home directories in UI messages (log file keeps full paths)
+    #[serde(default = "default_history_limit")]
+    pub history_limit: usize, // Entries kept in history, 0 = unlimited
+    #[serde(default)]
+    pub history_from_log_levels: Vec<LogLevel>, // e.g. ["error", "warn"]: such log lines also become LOG history entries
+    #[serde(default)]
+    pub min_log_level: LogLevel, // Lines below this reach neither the UI nor app.log
     
     // Deploy Config
     pub deploy_enabled: bool,
     #[serde(default)]
     pub servers: Vec<DeployServer>, // New: Multiple servers
+    #[serde(default)]
+    pub respect_folder_descriptor: bool, // Honor .deploy.json inside the copied folder
+    #[serde(default = "default_max_connections_per_host")]
+    pub max_connections_per_host: usize, // Open SSH sessions per host:port; keeps us under sshd's MaxStartups
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64, // TCP connect, SSH handshake and login each give up after this long
     
     // Legacy single server config (kept for migration/fallback)
     #[serde(default)]
@@ -72,6 +192,127 @@ pub struct AppConfig {
     pub remote_linux_path: String,
     
     pub post_commands: Vec<String>,
+    #[serde(default)]
+    pub post_copy_commands: Vec<String>, // Run locally after each copy, ${target} = copied folder
+    #[serde(default = "default_post_copy_command_timeout_secs")]
+    pub post_copy_command_timeout_secs: u64, // A post-copy command still running after this is killed, 0 = no limit
+    #[serde(default = "default_max_command_output_bytes")]
+    pub max_command_output_bytes: u64, // Post-command stdout+stderr kept per command, 0 = unlimited
+    #[serde(default = "default_command_output_flush_ms")]
+    pub command_output_flush_ms: u64, // Post-command output reaches the log in batches at most this often
+    // ${filename} resolution: how deep to look for .tar.gz and which one wins ("name" | "newest")
+    #[serde(default = "default_filename_search_depth")]
+    pub filename_search_depth: usize,
+    #[serde(default = "default_filename_pick")]
+    pub filename_pick: String,
+    #[serde(default)]
+    pub tar_upload: bool, // Upload one .tar.gz and extract remotely instead of file-by-file
+    #[serde(default, skip_serializing)]
+    pub compress_before_upload: Option<bool>, // Other name for tar_upload, folded into it on load
+    #[serde(default = "default_upload_retries")]
+    pub upload_retries: u32, // Reconnect-and-resume attempts after a failed file-by-file upload
+    // On by default: a truncated transfer must not pass as a successful deploy. Turn off on slow links.
+    #[serde(default = "default_true", alias = "verify_checksums")]
+    pub verify_upload: bool, // Hash every uploaded file remotely and compare with the local hash
+    #[serde(default = "default_remote_hash_command")]
+    pub remote_hash_command: String, // sha256sum or md5sum variant available on the server
+    #[serde(default)]
+    pub backup_before_deploy: bool, // Keep <target>.bak + manifest so rollback_deploy can restore
+    #[serde(default)]
+    pub mirror_delete: bool, // After uploading, delete remote files the local folder doesn't have
+    #[serde(default)]
+    pub rollback_commands: Vec<String>,
+    #[serde(default)]
+    pub remote_precheck_paths: Vec<String>, // e.g. stale lock/pid files checked before each deploy
+    #[serde(default)]
+    pub remote_precheck_remove: bool, // Delete the files found instead of just logging them
+
+    // Notifications
+    #[serde(default)]
+    pub webhook_url: Option<String>, // JSON POST after each scan and deploy
+    #[serde(default = "default_true")]
+    pub webhook_on_success: bool,
+    #[serde(default = "default_true")]
+    pub webhook_on_failure: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_tie_breaker() -> String {
+    "mtime".to_string()
+}
+
+/// Build folders named like `2026_02_11_09_30(1.3.9.P02)`.
+pub const DEFAULT_FOLDER_PATTERN: &str = r"^(\d{4}_\d{2}_\d{2}_\d{2}_\d{2})\((.+)\)$";
+
+fn default_folder_patterns() -> Vec<String> {
+    vec![DEFAULT_FOLDER_PATTERN.to_string()]
+}
+
+fn default_empty_versions_behavior() -> String {
+    "nothing".to_string()
+}
+
+fn default_max_concurrency() -> usize {
+    4
+}
+
+fn default_versions_to_keep() -> usize {
+    1
+}
+
+fn default_max_age_days() -> u32 {
+    1
+}
+
+fn default_history_limit() -> usize {
+    100
+}
+
+fn default_upload_retries() -> u32 {
+    3
+}
+
+fn default_command_output_flush_ms() -> u64 {
+    500
+}
+
+fn default_max_command_output_bytes() -> u64 {
+    1024 * 1024
+}
+
+fn default_post_copy_command_timeout_secs() -> u64 {
+    600
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_max_connections_per_host() -> usize {
+    2
+}
+
+fn default_copy_order() -> String {
+    "walk".to_string()
+}
+
+fn default_filename_search_depth() -> usize {
+    1
+}
+
+fn default_filename_pick() -> String {
+    "name".to_string()
+}
+
+fn default_remote_hash_command() -> String {
+    "sha256sum".to_string()
+}
+
+fn default_staging_max_age_hours() -> u64 {
+    24
 }
 
 impl Default for AppConfig {
@@ -80,64 +321,345 @@ impl Default for AppConfig {
             tasks: vec![],
             remote_paths: vec![],
             target_versions: vec![],
+            path_versions: HashMap::new(),
             local_path: "E:\\UMS_TEMP".to_string(),
+            additional_local_paths: vec![],
+            create_local_path_if_missing: true,
             interval_minutes: 10,
+            scheduler_enabled: false,
             time_ranges: vec![],
             file_extensions: vec![],
             filename_includes: vec![],
+            skip_hidden_files: true,
+            exclude_patterns: vec![],
+            pinned_folders: vec![],
+            max_age_days: default_max_age_days(),
+            versions_to_keep: default_versions_to_keep(),
+            folder_patterns: default_folder_patterns(),
+            datetime_format: String::new(),
+            default_version: String::new(),
+            empty_versions_behavior: default_empty_versions_behavior(),
+            tie_breaker: default_tie_breaker(),
+            copy_order: default_copy_order(),
+            force_overwrite_readonly: false,
+            max_bytes_per_sec: 0,
+            max_concurrency: default_max_concurrency(),
+            normalize_line_endings: None,
+            line_ending_extensions: vec![],
+            use_reflink: false,
+            detect_source_drift: false,
+            completion_sentinel: None,
+            move_instead_of_copy: false,
+            sync_mode: SyncMode::default(),
+            follow_symlinks: false,
+            preserve_ownership: false,
+            post_copy_delay_ms: 0,
+            per_file_timeout_secs: 0,
+            fsync_before_deploy: false,
+            staging_max_age_hours: default_staging_max_age_hours(),
+            safe_mode: false,
+            quiet_mode: false,
+            redact_paths: false,
+            history_limit: default_history_limit(),
+            history_from_log_levels: vec![],
+            min_log_level: LogLevel::default(),
             deploy_enabled: false,
             servers: vec![],
+            respect_folder_descriptor: false,
+            max_connections_per_host: default_max_connections_per_host(),
+            connect_timeout_secs: default_connect_timeout_secs(),
             ssh_host: "".to_string(),
             ssh_port: 22,
             ssh_user: "".to_string(),
             ssh_password: "".to_string(),
             remote_linux_path: "/tmp/upload".to_string(),
             post_commands: vec![],
+            post_copy_commands: vec![],
+            post_copy_command_timeout_secs: default_post_copy_command_timeout_secs(),
+            max_command_output_bytes: default_max_command_output_bytes(),
+            command_output_flush_ms: default_command_output_flush_ms(),
+            filename_search_depth: default_filename_search_depth(),
+            filename_pick: default_filename_pick(),
+            tar_upload: false,
+            compress_before_upload: None,
+            upload_retries: default_upload_retries(),
+            verify_upload: true,
+            remote_hash_command: default_remote_hash_command(),
+            backup_before_deploy: false,
+            mirror_delete: false,
+            rollback_commands: vec![],
+            remote_precheck_paths: vec![],
+            remote_precheck_remove: false,
+            webhook_url: None,
+            webhook_on_success: true,
+            webhook_on_failure: true,
+        }
+    }
+}
+
+/// Result of `load_config`. `error` means the file was unusable and defaults are in use;
+/// `warnings` are problems with an otherwise loaded config, e.g. a password that could not be decrypted.
+pub struct LoadedConfig {
+    pub config: AppConfig,
+    pub error: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+impl LoadedConfig {
+    fn defaults(error: Option<String>) -> Self {
+        Self { config: AppConfig::default(), error, warnings: vec![] }
+    }
+}
+
+/// Brings a config written by an older version (or with older key names) up to date.
+/// Used for config.json at startup and for imported files.
+pub fn migrate_legacy(config: &mut AppConfig) {
+    // Migration 1: If servers empty but legacy host exists, migrate it
+    if config.servers.is_empty() && !config.ssh_host.is_empty() {
+        config.servers.push(DeployServer {
+            id: uuid::Uuid::new_v4().to_string(),
+            enabled: true,
+            name: "Default Server".to_string(),
+            host: config.ssh_host.clone(),
+            port: config.ssh_port,
+            user: config.ssh_user.clone(),
+            password: config.ssh_password.clone(),
+            auth_method: AuthMethod::Password,
+            private_key_path: None,
+            passphrase: None,
+            encrypted: false,
+            remote_path: config.remote_linux_path.clone(),
+            staging_path: None,
+            remote_folder_name_template: String::new(),
+            deploy_single_artifact: None,
+        });
+    }
+    // Only migration 1 reads the legacy password; keeping it would leave it in plaintext on disk
+    if !config.servers.is_empty() {
+        config.ssh_password.clear();
+    }
+    
+    // Migration 2: Convert remote_paths/target_versions to tasks
+    if config.tasks.is_empty() && !config.remote_paths.is_empty() {
+        for (i, path) in config.remote_paths.iter().enumerate() {
+            if path.trim().is_empty() {
+                continue;
+            }
+            // Overridden paths only get their own versions, one task each
+            let versions = match config.path_versions.get(path) {
+                Some(list) if !list.is_empty() => list.clone(),
+                _ => vec![config.target_versions.get(i).cloned().unwrap_or_default()],
+            };
+            for (j, version) in versions.into_iter().enumerate() {
+                let name = if j == 0 {
+                    format!("Auto Task {}", i + 1)
+                } else {
+                    format!("Auto Task {}.{}", i + 1, j + 1)
+                };
+                config.tasks.push(ScanTask {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    enabled: true,
+                    name,
+                    remote_path: path.clone(),
+                    local_path: None,
+                    rule: MatchRule::VersionMatch(version),
+                });
+            }
         }
     }
+
+    // Migration 3: compress_before_upload turns tar_upload on; either key may be present
+    if let Some(compress) = config.compress_before_upload.take() {
+        config.tar_upload |= compress;
+    }
 }
 
-pub fn load_config(app_handle: &tauri::AppHandle) -> AppConfig {
+/// Loads the config, returning the error message when the file exists but can't be used.
+/// A corrupt file is backed up to `config.json.bak` before defaults take over.
+pub fn load_config(app_handle: &tauri::AppHandle) -> LoadedConfig {
     let config_path = get_config_path(app_handle);
-    if config_path.exists() {
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            if let Ok(mut config) = serde_json::from_str::<AppConfig>(&content) {
-                // Migration 1: If servers empty but legacy host exists, migrate it
-                if config.servers.is_empty() && !config.ssh_host.is_empty() {
-                    config.servers.push(DeployServer {
-                        id: uuid::Uuid::new_v4().to_string(),
-                        enabled: true,
-                        name: "Default Server".to_string(),
-                        host: config.ssh_host.clone(),
-                        port: config.ssh_port,
-                        user: config.ssh_user.clone(),
-                        password: config.ssh_password.clone(),
-                        remote_path: config.remote_linux_path.clone(),
-                    });
-                }
-                
-                // Migration 2: Convert remote_paths/target_versions to tasks
-                if config.tasks.is_empty() && !config.remote_paths.is_empty() {
-                    for (i, path) in config.remote_paths.iter().enumerate() {
-                        let version = config.target_versions.get(i).cloned().unwrap_or_default();
-                        if !path.trim().is_empty() {
-                            config.tasks.push(ScanTask {
-                                id: uuid::Uuid::new_v4().to_string(),
-                                enabled: true,
-                                name: format!("Auto Task {}", i + 1),
-                                remote_path: path.clone(),
-                                local_path: None,
-                                rule: MatchRule::VersionMatch(version),
-                            });
-                        }
-                    }
-                }
-                
-                return config;
+    if !config_path.exists() {
+        return LoadedConfig::defaults(None);
+    }
+
+    let content = match fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        Err(e) => {
+            let msg = format!("Failed to read {}: {}. Using default settings.", config_path.display(), e);
+            log::error!("{}", msg);
+            return LoadedConfig::defaults(Some(msg));
+        }
+    };
+
+    let mut config = match serde_json::from_str::<AppConfig>(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            let backup_path = config_path.with_extension("json.bak");
+            let backup_note = match fs::copy(&config_path, &backup_path) {
+                Ok(_) => format!("The corrupt file was backed up to {}.", backup_path.display()),
+                Err(copy_err) => format!("Backing it up to {} failed: {}.", backup_path.display(), copy_err),
+            };
+            let msg = format!("config.json is invalid ({}). Using default settings. {}", e, backup_note);
+            log::error!("{}", msg);
+            return LoadedConfig::defaults(Some(msg));
+        }
+    };
+
+    migrate_legacy(&mut config);
+
+    let warnings = crate::credentials::decrypt_loaded(&mut config);
+    for warning in &warnings {
+        log::warn!("{}", warning);
+    }
+    LoadedConfig { config, error: None, warnings }
+}
+
+/// Deploy targets are POSIX hosts, so a pasted Windows path (`C:\upload`, `\\share`) would
+/// produce a broken remote layout. Reject it up front with an actionable message.
+pub fn validate_remote_path(server: &DeployServer) -> Result<(), String> {
+    let staging = server.staging_path.as_deref().unwrap_or_default();
+    for path in [server.remote_path.as_str(), staging] {
+        validate_posix_path(server, path)?;
+    }
+    Ok(())
+}
+
+/// Same check for a remote path given outside the server entry, e.g. a manual deploy target.
+pub fn validate_posix_path(server: &DeployServer, path: &str) -> Result<(), String> {
+    let path = path.trim();
+    let bytes = path.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    if has_drive || path.contains('\\') {
+        return Err(format!(
+            "Server '{}': remote path '{}' looks like a Windows path. Deploy targets are Linux hosts, use a POSIX path such as /opt/deploy.",
+            server.name, path
+        ));
+    }
+    Ok(())
+}
+
+pub fn validate_config(config: &AppConfig) -> Result<(), String> {
+    for server in &config.servers {
+        validate_remote_path(server)?;
+    }
+    for pattern in &config.folder_patterns {
+        if let Err(e) = regex::Regex::new(pattern) {
+            return Err(format!("Folder pattern '{}' is not a valid regular expression: {}", pattern, e));
+        }
+    }
+    let datetime_format = config.datetime_format.trim();
+    if chrono::format::StrftimeItems::new(datetime_format).any(|item| matches!(item, chrono::format::Item::Error)) {
+        return Err(format!("Datetime format '{}' is not a valid chrono format (e.g. %Y%m%d-%H%M)", datetime_format));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct NormalizedExtensions {
+    pub extensions: Vec<String>, // Canonical, deduplicated, in input order
+    pub warnings: Vec<String>,   // One per entry that was dropped
+}
+
+/// `.EXE`, `*.exe` and `exe` all become `exe`; multi-part extensions like `tar.gz` are kept.
+pub fn normalize_extension(raw: &str) -> Result<String, String> {
+    let ext = raw.trim().trim_start_matches('*').trim_start_matches('.').to_lowercase();
+    if ext.is_empty() {
+        return Err(format!("\"{}\" is empty once wildcards and dots are removed", raw));
+    }
+    if ext.contains(['*', '?', '/', '\\']) || ext.chars().any(char::is_whitespace) || ext.ends_with('.') {
+        return Err(format!("\"{}\" is not a plain extension (e.g. exe or tar.gz)", raw));
+    }
+    Ok(ext)
+}
+
+pub fn normalize_extensions(exts: &[String]) -> NormalizedExtensions {
+    let mut normalized = NormalizedExtensions::default();
+    for raw in exts {
+        match normalize_extension(raw) {
+            Ok(ext) if !normalized.extensions.contains(&ext) => normalized.extensions.push(ext),
+            Ok(_) => {}
+            Err(e) => normalized.warnings.push(e),
+        }
+    }
+    normalized
+}
+
+/// Start and end of one `time_ranges` entry, "HH:mm-HH:mm" with optional spaces around either
+/// time. Shared by the scan schedule and the settings check so both read entries the same way.
+pub fn parse_time_range(range: &str) -> Option<(chrono::NaiveTime, chrono::NaiveTime)> {
+    let (start, end) = range.split_once('-')?;
+    let parse = |time: &str| chrono::NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+    parse(start).zip(parse(end))
+}
+
+/// Mistakes in `time_ranges` that still save fine but make the schedule confusing: entries the
+/// scanner can't parse or that never match, and ranges overlapping another one.
+pub fn validate_time_ranges(ranges: &[String]) -> Vec<String> {
+    let mut warnings = vec![];
+    let mut parsed: Vec<(&str, chrono::NaiveTime, chrono::NaiveTime)> = vec![];
+    for range in ranges {
+        match parse_time_range(range) {
+            None => warnings.push(format!("\"{}\" is not a HH:mm-HH:mm range and is ignored", range)),
+            Some((start, end)) if start == end => warnings.push(format!("\"{}\" starts and ends at the same minute", range)),
+            // Scans only check start <= now <= end, so a range can't wrap past midnight
+            Some((start, end)) if start > end => warnings.push(format!("\"{}\" ends before it starts and never matches; split ranges across midnight in two", range)),
+            Some((start, end)) => parsed.push((range, start, end)),
+        }
+    }
+    for (i, (range, start, end)) in parsed.iter().enumerate() {
+        for (other, other_start, other_end) in &parsed[i + 1..] {
+            if start < other_end && other_start < end {
+                warnings.push(format!("\"{}\" overlaps \"{}\"", range, other));
             }
         }
     }
-    AppConfig::default()
+    warnings
+}
+
+const REDACTED: &str = "********";
+
+/// Copy of `config` that is safe to show or export: passwords and the webhook URL
+/// (which usually embeds a token) are masked, everything else is left as-is.
+pub fn redact_secrets(config: &AppConfig) -> AppConfig {
+    let mask = |value: &str| if value.is_empty() { String::new() } else { REDACTED.to_string() };
+    let mut redacted = config.clone();
+    redacted.ssh_password = mask(&redacted.ssh_password);
+    for server in redacted.servers.iter_mut() {
+        server.password = mask(&server.password);
+        server.passphrase = server.passphrase.as_deref().map(mask);
+    }
+    redacted.webhook_url = redacted.webhook_url.as_deref().map(mask);
+    redacted
+}
+
+/// Copy of `config` for sharing: passwords, passphrases and the webhook URL are removed
+/// rather than masked, so importing it elsewhere doesn't save the mask as a password.
+pub fn strip_secrets(config: &AppConfig) -> AppConfig {
+    let mut stripped = config.clone();
+    stripped.ssh_password.clear();
+    for server in stripped.servers.iter_mut() {
+        server.password.clear();
+        server.passphrase = None;
+    }
+    stripped.webhook_url = None;
+    stripped
+}
+
+/// What scans actually run with: in safe mode every switch that could touch a server or
+/// delete something is forced off, whatever it is set to individually.
+pub fn apply_safe_mode(config: &AppConfig) -> AppConfig {
+    let mut effective = config.clone();
+    if effective.safe_mode {
+        effective.deploy_enabled = false;
+        effective.post_commands.clear();
+        effective.post_copy_commands.clear();
+        effective.rollback_commands.clear();
+        effective.backup_before_deploy = false;
+        effective.mirror_delete = false;
+        effective.remote_precheck_remove = false;
+        effective.move_instead_of_copy = false;
+    }
+    effective
 }
 
 pub fn save_config(app_handle: &tauri::AppHandle, config: &AppConfig) -> Result<(), String> {
@@ -145,15 +667,47 @@ pub fn save_config(app_handle: &tauri::AppHandle, config: &AppConfig) -> Result<
     if let Some(parent) = config_path.parent() {
         fs::create_dir_all(parent).map_err(|e| e.to_string())?;
     }
-    let content = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    let content = serde_json::to_string_pretty(&crate::credentials::encrypt_for_storage(config)).map_err(|e| e.to_string())?;
     fs::write(config_path, content).map_err(|e| e.to_string())?;
     Ok(())
 }
 
 pub fn get_log_path(app_handle: &tauri::AppHandle) -> PathBuf {
-    app_handle.path().app_data_dir().unwrap().join("app.log")
+    app_data_dir(app_handle).join("app.log")
 }
 
 pub fn get_config_path(app_handle: &tauri::AppHandle) -> PathBuf {
-    app_handle.path().app_config_dir().unwrap().join("config.json")
+    app_config_dir(app_handle).join("config.json")
+}
+
+// Overrides where files go when the OS can't tell us the app directories
+const FALLBACK_DIR_ENV: &str = "FILE_SYNC_TOOL_HOME";
+
+pub fn app_data_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> PathBuf {
+    app_handle.path().app_data_dir().unwrap_or_else(|e| fallback_dir("app_data_dir", e))
+}
+
+pub fn app_config_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> PathBuf {
+    app_handle.path().app_config_dir().unwrap_or_else(|e| fallback_dir("app_config_dir", e))
+}
+
+// Locked-down systems sometimes can't resolve the app directories. Rather than crash at startup,
+// use $FILE_SYNC_TOOL_HOME, else a folder next to the executable, else the temp dir.
+fn fallback_dir(which: &str, err: tauri::Error) -> PathBuf {
+    static FALLBACK: std::sync::OnceLock<PathBuf> = std::sync::OnceLock::new();
+    static WARNED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+    let dir = FALLBACK.get_or_init(|| {
+        let from_env = std::env::var_os(FALLBACK_DIR_ENV).map(PathBuf::from);
+        let next_to_exe = std::env::current_exe().ok()
+            .and_then(|exe| exe.parent().map(|p| p.join("file-sync-tool-data")));
+        from_env.into_iter()
+            .chain(next_to_exe)
+            .find(|dir| fs::create_dir_all(dir).is_ok())
+            .unwrap_or_else(|| std::env::temp_dir().join("file-sync-tool"))
+    });
+    if !WARNED.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        log::warn!("{} unavailable ({}); using {} instead", which, err, dir.display());
+    }
+    dir.clone()
 }
@@ -0,0 +1,112 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+
+use crate::config::AppConfig;
+
+const KEYRING_SERVICE: &str = "File-Sync-Tool";
+const KEYRING_USER: &str = "config-encryption-key";
+const NONCE_LEN: usize = 12;
+
+// The key lives in the OS keyring (Credential Manager, Keychain, Secret Service) and is
+// created on first use, so config.json alone is not enough to recover the passwords
+fn cipher() -> Result<Aes256Gcm, String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|e| e.to_string())?;
+    let key = match entry.get_password() {
+        Ok(stored) => decode_hex(&stored)
+            .filter(|bytes| bytes.len() == 32)
+            .ok_or_else(|| "the key stored in the keyring is malformed".to_string())?,
+        Err(keyring::Error::NoEntry) => {
+            let key = Aes256Gcm::generate_key(OsRng);
+            entry.set_password(&encode_hex(&key)).map_err(|e| e.to_string())?;
+            key.to_vec()
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+fn encrypt(cipher: &Aes256Gcm, plaintext: &str) -> Result<String, String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, plaintext.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(encode_hex(&[nonce.as_slice(), &ciphertext].concat()))
+}
+
+fn decrypt(cipher: &Aes256Gcm, stored: &str) -> Result<String, String> {
+    let bytes = decode_hex(stored).filter(|b| b.len() > NONCE_LEN).ok_or("not an encrypted value")?;
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    let plaintext = cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "wrong key or corrupted value".to_string())?;
+    String::from_utf8(plaintext).map_err(|e| e.to_string())
+}
+
+/// Copy of `config` as it should be written to disk: server passwords and key passphrases
+/// encrypted and each such server marked `encrypted`. Without a usable keyring the copy keeps
+/// plaintext (with a warning), so saving never fails because of it.
+pub fn encrypt_for_storage(config: &AppConfig) -> AppConfig {
+    let mut stored = config.clone();
+    if stored.servers.iter().all(|s| s.password.is_empty() && s.passphrase.as_deref().unwrap_or_default().is_empty()) {
+        return stored;
+    }
+    let cipher = match cipher() {
+        Ok(cipher) => cipher,
+        Err(e) => {
+            log::warn!("OS keyring unavailable ({}); server passwords are saved in plaintext", e);
+            return stored;
+        }
+    };
+    for server in stored.servers.iter_mut() {
+        let password = encrypt(&cipher, &server.password);
+        let passphrase = server.passphrase.as_deref().map(|p| encrypt(&cipher, p)).transpose();
+        match (password, passphrase) {
+            (Ok(password), Ok(passphrase)) => {
+                server.password = password;
+                server.passphrase = passphrase;
+                server.encrypted = true;
+            }
+            (Err(e), _) | (_, Err(e)) => log::warn!("Could not encrypt the password of {}: {}; saved in plaintext", server.name, e),
+        }
+    }
+    stored
+}
+
+/// Decrypts the servers `encrypt_for_storage` marked, in place. A server that can't be decrypted
+/// (keyring gone, config copied from another machine) loses its password rather than the config
+/// failing to load; the returned warnings say which.
+pub fn decrypt_loaded(config: &mut AppConfig) -> Vec<String> {
+    let mut warnings = vec![];
+    if !config.servers.iter().any(|s| s.encrypted) {
+        return warnings;
+    }
+    let cipher = cipher();
+    for server in config.servers.iter_mut().filter(|s| s.encrypted) {
+        server.encrypted = false;
+        let decrypted = cipher.as_ref().map_err(String::clone).and_then(|cipher| {
+            let password = decrypt(cipher, &server.password)?;
+            let passphrase = server.passphrase.as_deref().map(|p| decrypt(cipher, p)).transpose()?;
+            Ok((password, passphrase))
+        });
+        match decrypted {
+            Ok((password, passphrase)) => {
+                server.password = password;
+                server.passphrase = passphrase;
+            }
+            Err(e) => {
+                server.password.clear();
+                server.passphrase = None;
+                warnings.push(format!("Could not decrypt the password of server {} ({}); please enter it again", server.name, e));
+            }
+        }
+    }
+    warnings
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
@@ -1,21 +1,54 @@
-use crate::config::{AppConfig, DeployServer};
-use std::net::TcpStream;
-use std::path::Path;
+use crate::config::{AppConfig, AuthMethod, DeployServer};
+use crate::timeline::record_sample;
+use crate::history::{add_history_entry, HistoryEntry};
+use crate::logging::{current_run_id, level_enabled, record_log, ui_message, LogLevel};
+use crate::webhook;
+use crate::AppState;
+use crate::limits::{host_connections, live_limits, HostPermit, Throttle};
+use crate::walk::{SymlinkGuard, WalkEntry};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
 use ssh2::Session;
 use std::io::{Read, Write};
 use std::fs;
-use tauri::Emitter;
-use std::time::Instant;
+use tauri::{Emitter, Manager};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, serde::Serialize, Clone)]
 struct LogEvent {
     msg: String,
-    level: String,
+    level: LogLevel,
 }
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 
+pub const FOLDER_DESCRIPTOR_NAME: &str = ".deploy.json";
+
+/// Optional `.deploy.json` shipped inside a build folder by its producer.
+/// Every field is optional; missing fields fall back to the app config.
+#[derive(Debug, serde::Deserialize, Clone, Default)]
+struct FolderDescriptor {
+    #[serde(default)]
+    servers: Option<Vec<String>>, // Server ids to deploy to
+    #[serde(default)]
+    remote_path: Option<String>,
+    #[serde(default)]
+    post_commands: Option<Vec<String>>,
+}
+
+fn read_folder_descriptor(local_folder_path: &Path) -> Result<Option<FolderDescriptor>, String> {
+    let path = local_folder_path.join(FOLDER_DESCRIPTOR_NAME);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Invalid {}: {}", FOLDER_DESCRIPTOR_NAME, e))
+}
+
 #[derive(Debug, serde::Serialize, Clone)]
 struct ProgressEvent {
     folder: String,
@@ -29,10 +62,15 @@ struct ProgressEvent {
     remote_path: String,
 }
 
-fn emit_log<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, msg: String, level: &str) {
+fn emit_log<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, msg: String, level: LogLevel) {
+    if !level_enabled(app_handle, level) {
+        return;
+    }
+    let msg = ui_message(app_handle, msg);
+    record_log(app_handle, level, "deploy", &msg);
     let _ = app_handle.emit("log-message", LogEvent {
         msg,
-        level: level.to_string(),
+        level,
     });
 }
 
@@ -66,18 +104,601 @@ fn emit_progress<R: tauri::Runtime>(
     });
 }
 
-pub fn check_connection(server: &DeployServer) -> Result<String, String> {
-    let tcp = TcpStream::connect(format!("{}:{}", server.host, server.port))
-        .map_err(|e| format!("TCP Connect failed to {}: {}", server.host, e))?;
-    
-    let mut sess = Session::new().unwrap();
+/// State shared by the whole upload of one folder: byte counters plus per-upload options.
+struct UploadContext {
+    total_size: u64,
+    copied_bytes: u64,
+    start_time: Instant,
+    last_emit_time: Instant,
+    local_path_str: String,
+    remote_path_display: String,
+    quiet: bool, // Skip copy-progress events (quiet_mode)
+    timeline_key: String, // Folder the speed samples are recorded under
+    verify_command: Option<String>, // Remote hash command when verify_upload is on
+    throttle: Throttle,
+    checkpoint: Option<DeployCheckpoint>, // Set for folder uploads that can be resumed
+    links: SymlinkGuard, // follow_symlinks, rooted at the local path being uploaded
+    session: Session, // Sent keepalives while the upload is paused
+}
+
+impl UploadContext {
+    fn new<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, sess: &Session, total_size: u64, local_path_str: &str, remote_path_display: &str, config: &AppConfig) -> Self {
+        Self {
+            total_size,
+            copied_bytes: 0,
+            start_time: Instant::now(),
+            last_emit_time: Instant::now(),
+            local_path_str: local_path_str.to_string(),
+            remote_path_display: remote_path_display.to_string(),
+            quiet: config.quiet_mode,
+            timeline_key: Path::new(local_path_str).file_name().unwrap_or_default().to_string_lossy().to_string(),
+            verify_command: if config.verify_upload { Some(config.remote_hash_command.clone()) } else { None },
+            throttle: Throttle::new(live_limits(app_handle, config)),
+            checkpoint: None,
+            links: SymlinkGuard::new(Path::new(local_path_str), config.follow_symlinks),
+            session: sess.clone(),
+        }
+    }
+
+    // Throttled to one event per 200ms unless forced
+    fn report<R: tauri::Runtime>(&mut self, app_handle: &tauri::AppHandle<R>, folder: &str, force: bool) {
+        let now = Instant::now();
+        if !force && now.duration_since(self.last_emit_time).as_millis() <= 200 {
+            return;
+        }
+        self.last_emit_time = now;
+        record_sample(app_handle, &self.timeline_key, self.start_time.elapsed().as_millis() as u64, self.copied_bytes, self.total_size);
+        if self.quiet {
+            return;
+        }
+        let elapsed = self.start_time.elapsed().as_secs_f64();
+        let speed = if elapsed > 0.0 {
+            (self.copied_bytes as f64 / elapsed) as u64
+        } else {
+            0
+        };
+        let eta = if speed > 0 && self.total_size > self.copied_bytes {
+            (self.total_size - self.copied_bytes) / speed
+        } else {
+            0
+        };
+        
+        emit_progress(
+            app_handle, 
+            folder,
+            self.copied_bytes, 
+            self.total_size, 
+            speed, 
+            eta,
+            elapsed as u64,
+            &self.local_path_str,
+            &self.remote_path_display
+        );
+    }
+}
+
+// Connects and authenticates, holding one of the host's connection slots for as long as the permit lives
+fn open_session<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    server: &DeployServer,
+    should_cancel: &AtomicBool
+) -> Result<(Session, HostPermit), String> {
+    let permit = host_connections(app_handle).acquire(&format!("{}:{}", server.host, server.port), should_cancel)?;
+    let connect_timeout = app_handle.try_state::<AppState>()
+        .map(|state| state.read_config(|c| c.connect_timeout_secs))
+        .unwrap_or_else(|| AppConfig::default().connect_timeout_secs);
+    let sess = connect_session(server, connect_timeout)?;
+    authenticate(&sess, server)?;
+    // Uploads and post commands can legitimately block for longer than a login should
+    sess.set_timeout(0);
+    Ok((sess, permit))
+}
+
+const TIMED_OUT: &str = "Timed out";
+const KEEPALIVE_INTERVAL_SECS: u32 = 30;
+
+/// TCP connect and SSH handshake, each bounded by `timeout_secs` (which also stays set for
+/// the login), with keepalives so idle-sensitive firewalls don't drop long uploads.
+fn connect_session(server: &DeployServer, timeout_secs: u64) -> Result<Session, String> {
+    let timeout = Duration::from_secs(timeout_secs.max(1));
+    let addrs = (server.host.as_str(), server.port).to_socket_addrs()
+        .map_err(|e| format!("Cannot resolve {}: {}", server.host, e))?;
+    let mut last_error = format!("No address found for {}", server.host);
+    let mut tcp = None;
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => {
+                tcp = Some(stream);
+                break;
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                last_error = format!("{} connecting to {}:{} after {}s (host unreachable or port filtered?)", TIMED_OUT, server.host, server.port, timeout.as_secs());
+            }
+            Err(e) => last_error = format!("TCP Connect failed to {}: {}", server.host, e),
+        }
+    }
+    let tcp = tcp.ok_or(last_error)?;
+
+    let mut sess = Session::new().map_err(|e| e.to_string())?;
     sess.set_tcp_stream(tcp);
-    sess.handshake().map_err(|e| format!("SSH Handshake failed: {}", e))?;
+    sess.set_timeout(timeout.as_millis() as u32);
+    sess.handshake().map_err(|e| describe_ssh_error("SSH Handshake failed", &e, timeout_secs))?;
+    sess.set_keepalive(true, KEEPALIVE_INTERVAL_SECS);
+    Ok(sess)
+}
+
+// libssh2 reports an expired session timeout as LIBSSH2_ERROR_TIMEOUT (-9)
+fn describe_ssh_error(context: &str, e: &ssh2::Error, timeout_secs: u64) -> String {
+    if matches!(e.code(), ssh2::ErrorCode::Session(-9)) {
+        format!("{}: server did not respond within {}s", TIMED_OUT, timeout_secs)
+    } else {
+        format!("{}: {}", context, e)
+    }
+}
+
+/// Logs in with the server's configured method and returns its name for display.
+fn authenticate(sess: &Session, server: &DeployServer) -> Result<&'static str, String> {
+    match server.auth_method {
+        AuthMethod::Password => {
+            sess.userauth_password(&server.user, &server.password).map_err(|e| describe_auth_error(sess, &e))?;
+            Ok("password")
+        },
+        AuthMethod::PublicKey => {
+            let key_path = server.private_key_path.as_deref().map(str::trim).filter(|p| !p.is_empty())
+                .ok_or_else(|| format!("No private key file configured for {}", server.name))?;
+            if !Path::new(key_path).is_file() {
+                return Err(format!("Private key file not found: {}", key_path));
+            }
+            let passphrase = server.passphrase.as_deref().filter(|p| !p.is_empty());
+            sess.userauth_pubkey_file(&server.user, None, Path::new(key_path), passphrase)
+                .map_err(|e| describe_auth_error(sess, &e))?;
+            Ok("public key")
+        },
+    }
+}
+
+// A login that times out is a slow or stuck server, not a wrong password
+fn describe_auth_error(sess: &Session, e: &ssh2::Error) -> String {
+    let timeout_secs = u64::from(sess.timeout()) / 1000;
+    if matches!(e.code(), ssh2::ErrorCode::Session(-9)) {
+        describe_ssh_error("Authentication", e, timeout_secs)
+    } else {
+        e.to_string()
+    }
+}
+
+pub fn check_connection(server: &DeployServer, connect_timeout_secs: u64) -> Result<String, String> {
+    let (_sess, method) = connect_and_authenticate(server, connect_timeout_secs)?;
+    Ok(format!("Connected to {} ({} authentication)", server.name, method))
+}
+
+fn connect_and_authenticate(server: &DeployServer, connect_timeout_secs: u64) -> Result<(Session, &'static str), String> {
+    let sess = connect_session(server, connect_timeout_secs)?;
     
-    sess.userauth_password(&server.user, &server.password)
-        .map_err(|e| format!("Authentication failed: {}", e))?;
+    let method = authenticate(&sess, server)
+        .map_err(|e| if e.starts_with(TIMED_OUT) { e } else { format!("Authentication failed: {}", e) })?;
+    Ok((sess, method))
+}
+
+/// `check_connection` plus what a deploy needs next: creating (and removing) a scratch folder
+/// under `remote_path`, and the free space there according to `df`. Failing to write is an
+/// error of its own; an unknown free space is only mentioned.
+pub fn check_remote_path(server: &DeployServer, connect_timeout_secs: u64) -> Result<String, String> {
+    let (sess, method) = connect_and_authenticate(server, connect_timeout_secs)?;
+    let connected = format!("Connected to {} ({} authentication)", server.name, method);
+    let remote_path = server.remote_path.trim_end_matches('/');
+    if remote_path.is_empty() {
+        return Err(format!("{}, but no remote path is configured", connected));
+    }
+
+    let sftp = sess.sftp().map_err(|e| format!("{}, but SFTP init failed: {}", connected, e))?;
+    let probe_dir = format!("{}/.file-sync-write-test-{}", remote_path, &uuid::Uuid::new_v4().simple().to_string()[..8]);
+    sftp.mkdir(Path::new(&probe_dir), 0o755).map_err(|e| match e.code() {
+        ssh2::ErrorCode::SFTP(2) => format!("{}, but {} does not exist", connected, remote_path),
+        ssh2::ErrorCode::SFTP(3) => format!("{}, but {} has no write permission on {}", connected, server.user, remote_path),
+        _ => format!("{}, but creating a folder in {} failed: {}", connected, remote_path, e),
+    })?;
+    let mut report = format!("{}; {} is writable", connected, remote_path);
+    if let Err(e) = sftp.rmdir(Path::new(&probe_dir)) {
+        report.push_str(&format!(" (could not remove the test folder {}: {})", probe_dir, e));
+    }
+
+    match remote_free_bytes(&sess, remote_path) {
+        Ok(free) => report.push_str(&format!("; {:.1} GiB free", free as f64 / (1024.0 * 1024.0 * 1024.0))),
+        Err(e) => report.push_str(&format!("; free space unknown ({})", e)),
+    }
+    Ok(report)
+}
+
+// POSIX `df -Pk` output: a header, then "filesystem 1024-blocks used available capacity mount"
+fn remote_free_bytes(sess: &Session, remote_path: &str) -> Result<u64, String> {
+    let (output, status) = run_remote_command(sess, &format!("df -Pk {} 2>&1", shell_quote(remote_path)))?;
+    if status != 0 {
+        return Err(format!("df exited with {}: {}", status, output.trim()));
+    }
+    output.lines().nth(1)
+        .and_then(|line| line.split_whitespace().nth(3))
+        .and_then(|available| available.parse::<u64>().ok())
+        .map(|kib| kib * 1024)
+        .ok_or_else(|| format!("unexpected df output: {}", output.trim()))
+}
+
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct ServerProbe {
+    pub banner: String,
+    pub host_key_type: String,
+    pub host_key_fingerprint: String, // SHA-256, colon separated hex
+    pub authenticated: bool,
+    pub auth_error: Option<String>,
+    pub sftp_ok: bool,
+    pub sftp_error: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub mtime: Option<u64>, // Unix seconds
+}
+
+/// SFTP listing of `path` for the remote folder browser: directories first, then by name.
+pub fn list_remote_dir<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    server: &DeployServer,
+    path: &str
+) -> Result<Vec<RemoteEntry>, String> {
+    let (sess, _permit) = open_session(app_handle, server, &AtomicBool::new(false))?;
+    let sftp = sess.sftp().map_err(|e| format!("SFTP init failed: {}", e))?;
+
+    let path = if path.trim().is_empty() { "." } else { path.trim() };
+    let listing = sftp.readdir(Path::new(path)).map_err(|e| match e.code() {
+        ssh2::ErrorCode::SFTP(2) => format!("{} does not exist on {}", path, server.name),
+        ssh2::ErrorCode::SFTP(3) => format!("Permission denied: {} can't read {} on {}", server.user, path, server.name),
+        _ => format!("Failed to list {} on {}: {}", path, server.name, e),
+    })?;
+
+    let mut entries: Vec<RemoteEntry> = listing.into_iter()
+        .filter_map(|(entry_path, stat)| {
+            let name = entry_path.file_name()?.to_string_lossy().to_string();
+            Some(RemoteEntry { name, is_dir: stat.is_dir(), size: stat.size.unwrap_or(0), mtime: stat.mtime })
+        })
+        .collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+/// Like `check_connection`, but reports what the server offers instead of stopping at auth.
+/// Useful for "auth works but upload fails" cases.
+pub fn probe_server(server: &DeployServer, connect_timeout_secs: u64) -> Result<ServerProbe, String> {
+    let sess = connect_session(server, connect_timeout_secs)?;
+
+    let banner = sess.banner().unwrap_or_default().to_string();
+    let host_key_type = sess.host_key()
+        .map(|(_, key_type)| format!("{:?}", key_type))
+        .unwrap_or_default();
+    let host_key_fingerprint = sess.host_key_hash(ssh2::HashType::Sha256)
+        .map(|hash| hash.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(":"))
+        .unwrap_or_default();
+
+    let mut probe = ServerProbe {
+        banner,
+        host_key_type,
+        host_key_fingerprint,
+        authenticated: false,
+        auth_error: None,
+        sftp_ok: false,
+        sftp_error: None,
+    };
+
+    if let Err(e) = authenticate(&sess, server) {
+        probe.auth_error = Some(e);
+        return Ok(probe);
+    }
+    probe.authenticated = true;
+
+    match sess.sftp() {
+        Ok(_) => probe.sftp_ok = true,
+        Err(e) => probe.sftp_error = Some(e.to_string()),
+    }
     
-    Ok(format!("Connected to {}", server.name))
+    Ok(probe)
+}
+
+const CHECKPOINT_DIR: &str = "deploy-checkpoints";
+
+/// Files of a folder upload that are already complete on the server, appended one line per file
+/// (`size \t mtime \t remote path`) as each upload finishes. If the process dies mid-deploy, the
+/// next deploy of the same folder to the same server skips exactly those files. Removed once the
+/// whole folder is up.
+struct DeployCheckpoint {
+    path: PathBuf,
+    done: HashMap<String, (u64, u64)>,
+    file: Option<fs::File>,
+}
+
+impl DeployCheckpoint {
+    fn path_for<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, server: &DeployServer, remote_target: &str) -> PathBuf {
+        use sha2::Digest;
+        let key = format!("{}\n{}:{}\n{}", server.id, server.host, server.port, remote_target);
+        let digest = sha2::Sha256::digest(key.as_bytes());
+        let name: String = digest.iter().take(16).map(|b| format!("{:02x}", b)).collect();
+        crate::config::app_data_dir(app_handle).join(CHECKPOINT_DIR).join(format!("{}.txt", name))
+    }
+
+    fn open<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, server: &DeployServer, remote_target: &str) -> Self {
+        let path = Self::path_for(app_handle, server, remote_target);
+        let done = fs::read_to_string(&path).unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                let size = parts.next()?.parse().ok()?;
+                let mtime = parts.next()?.parse().ok()?;
+                Some((parts.next()?.to_string(), (size, mtime)))
+            })
+            .collect();
+        Self { path, done, file: None }
+    }
+
+    fn is_resuming(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    fn is_done(&self, remote_file: &str, size: u64, mtime: u64) -> bool {
+        self.done.get(remote_file) == Some(&(size, mtime))
+    }
+
+    // Best effort: a checkpoint that can't be written only costs re-uploading on resume
+    fn record(&mut self, remote_file: &str, size: u64, mtime: u64) {
+        if self.file.is_none() {
+            let opened = self.path.parent().map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::OpenOptions::new().create(true).append(true).open(&self.path));
+            match opened {
+                Ok(file) => self.file = Some(file),
+                Err(e) => {
+                    log::warn!("Could not write deploy checkpoint {}: {}", self.path.display(), e);
+                    return;
+                }
+            }
+        }
+        if let Some(file) = self.file.as_mut() {
+            let _ = writeln!(file, "{}\t{}\t{}", size, mtime, remote_file);
+        }
+    }
+
+    fn finish(self) {
+        drop(self.file);
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn mtime_secs(metadata: &fs::Metadata) -> u64 {
+    metadata.modified().ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn remote_target_for(server: &DeployServer, folder_name: &str) -> String {
+    format!("{}/{}", server.remote_path.trim_end_matches('/'), remote_folder_name(server, folder_name))
+}
+
+// Where the files are actually written: the staging dir when one is set, else the live target
+fn upload_target_for(server: &DeployServer, folder_name: &str) -> String {
+    match server.staging_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(staging) => format!("{}/{}", staging.trim_end_matches('/'), remote_folder_name(server, folder_name)),
+        None => remote_target_for(server, folder_name),
+    }
+}
+
+/// Deploys `local_path` to `server` again after an interrupted deploy, skipping the files the
+/// checkpoint says are already there. Fails if there is nothing to resume.
+pub fn deploy_resume<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
+    server: &DeployServer,
+    local_path: &str,
+    should_cancel: Arc<AtomicBool>,
+    is_paused: Arc<AtomicBool>
+) -> Result<(), String> {
+    let local = Path::new(local_path);
+    let folder_name = local.file_name().unwrap_or_default().to_string_lossy().to_string();
+    if !DeployCheckpoint::path_for(app_handle, server, &upload_target_for(server, &folder_name)).exists() {
+        return Err(format!("No interrupted deploy of {} to {} to resume", folder_name, server.name));
+    }
+    let total_size = calculate_size(app_handle, local, config.follow_symlinks, &should_cancel)?;
+    let outcome = deploy_single_server(app_handle, config, server, local, &folder_name, &config.post_commands, total_size, should_cancel, is_paused);
+    record_deploy_history(app_handle, server, &folder_name, local, &remote_target_for(server, &folder_name), &outcome);
+    outcome.map(|_| ())
+}
+
+/// Cancel/pause flags for a deploy started by a scan: set when either the scan's own flags or
+/// the deploy controls (`cancel_deploy`/`pause_deploy`) are, so a deploy can be stopped without
+/// cancelling the rest of the scan. Also marks the app as deploying while it lives.
+struct DeployControls {
+    cancel: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    done: Arc<AtomicBool>,
+    is_deploying: Option<Arc<AtomicBool>>, // Set when this deploy claimed the flag and must release it
+}
+
+impl DeployControls {
+    fn attach<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, scan_cancel: &Arc<AtomicBool>, scan_paused: &Arc<AtomicBool>) -> Self {
+        let Some(state) = app_handle.try_state::<AppState>() else {
+            return Self { cancel: scan_cancel.clone(), paused: scan_paused.clone(), done: Arc::new(AtomicBool::new(true)), is_deploying: None };
+        };
+        let claimed = !state.is_deploying.swap(true, Ordering::SeqCst);
+        if claimed {
+            state.deploy_cancel.store(false, Ordering::SeqCst);
+            state.deploy_paused.store(false, Ordering::SeqCst);
+        }
+
+        let controls = Self {
+            cancel: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            done: Arc::new(AtomicBool::new(false)),
+            is_deploying: claimed.then(|| state.is_deploying.clone()),
+        };
+        let (cancel, paused, done) = (controls.cancel.clone(), controls.paused.clone(), controls.done.clone());
+        let (scan_cancel, scan_paused) = (scan_cancel.clone(), scan_paused.clone());
+        let (deploy_cancel, deploy_paused) = (state.deploy_cancel.clone(), state.deploy_paused.clone());
+        // Same 100ms granularity the transfer loops poll at
+        std::thread::spawn(move || {
+            while !done.load(Ordering::SeqCst) {
+                if scan_cancel.load(Ordering::SeqCst) || deploy_cancel.load(Ordering::SeqCst) {
+                    cancel.store(true, Ordering::SeqCst);
+                }
+                paused.store(scan_paused.load(Ordering::SeqCst) || deploy_paused.load(Ordering::SeqCst), Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(100));
+            }
+        });
+        controls
+    }
+}
+
+impl Drop for DeployControls {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::SeqCst);
+        if let Some(is_deploying) = &self.is_deploying {
+            is_deploying.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+fn skipped_for_run<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, server_id: &str) -> bool {
+    app_handle.try_state::<AppState>()
+        .is_some_and(|state| state.skipped_servers.lock().unwrap_or_else(|e| e.into_inner()).contains(server_id))
+}
+
+/// Forgets the servers excluded with `disable_server_for_run`; called when a batch ends (a whole
+/// scan, or one deploy command), never per folder.
+pub fn clear_skipped_servers<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) {
+    if let Some(state) = app_handle.try_state::<AppState>() {
+        state.skipped_servers.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+const FAILED_SERVERS_FILE: &str = "failed-servers.json";
+
+// Server ids whose last deploy of each local folder failed, keyed by local folder path
+fn load_failed_servers<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> HashMap<String, Vec<String>> {
+    let path = crate::config::app_data_dir(app_handle).join(FAILED_SERVERS_FILE);
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_failed_servers<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, local_folder_path: &Path, failed_ids: Vec<String>) {
+    let mut failed = load_failed_servers(app_handle);
+    let key = local_folder_path.to_string_lossy().to_string();
+    if failed_ids.is_empty() {
+        if failed.remove(&key).is_none() {
+            return;
+        }
+    } else {
+        failed.insert(key, failed_ids);
+    }
+    let dir = crate::config::app_data_dir(app_handle);
+    let _ = fs::create_dir_all(&dir);
+    if let Err(e) = fs::write(dir.join(FAILED_SERVERS_FILE), serde_json::to_string_pretty(&failed).unwrap_or_default()) {
+        log::warn!("Failed to save {}: {}", FAILED_SERVERS_FILE, e);
+    }
+}
+
+#[derive(Debug, serde::Serialize, Clone, Default)]
+pub struct RetryOutcome {
+    pub succeeded: Vec<String>, // Server names
+    pub failed: Vec<String>,
+}
+
+/// Deploys `local_path` again, but only to the servers whose last deploy of it failed.
+/// Servers that fail again stay recorded for the next retry.
+pub fn retry_failed_servers<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
+    local_path: &str,
+    folder_name: &str,
+    should_cancel: Arc<AtomicBool>,
+    is_paused: Arc<AtomicBool>
+) -> Result<RetryOutcome, String> {
+    let local = Path::new(local_path);
+    let failed_ids = load_failed_servers(app_handle).remove(local_path).unwrap_or_default();
+    if failed_ids.is_empty() {
+        return Err(format!("No failed servers recorded for {}", folder_name));
+    }
+
+    let (servers, post_commands) = resolve_targets(app_handle, config, local, folder_name);
+    let total_size = calculate_size(app_handle, local, config.follow_symlinks, &should_cancel)?;
+    let mut outcome = RetryOutcome::default();
+    let mut still_failed = vec![];
+
+    for server in servers.iter().filter(|s| failed_ids.contains(&s.id)) {
+        if should_cancel.load(Ordering::SeqCst) {
+            still_failed.push(server.id.clone());
+            outcome.failed.push(server.name.clone());
+            continue;
+        }
+        emit_log(app_handle, format!("[{}] Retrying failed deployment of {}", server.name, folder_name), LogLevel::Info);
+        let result = deploy_single_server(app_handle, config, server, local, folder_name, &post_commands, total_size, should_cancel.clone(), is_paused.clone());
+        record_deploy_history(app_handle, server, folder_name, local, &remote_target_for(server, folder_name), &result);
+        match result {
+            Ok(_) => {
+                emit_log(app_handle, format!("[{}] Deployment successful", server.name), LogLevel::Success);
+                outcome.succeeded.push(server.name.clone());
+            },
+            Err(e) => {
+                emit_log(app_handle, format!("[{}] Deployment failed: {}", server.name, e), LogLevel::Error);
+                still_failed.push(server.id.clone());
+                outcome.failed.push(server.name.clone());
+            }
+        }
+    }
+
+    // Servers removed from the config (or by the descriptor) since are dropped from the set
+    save_failed_servers(app_handle, local, still_failed);
+    Ok(outcome)
+}
+
+// Servers and post commands for a folder, after its `.deploy.json` (if honoured) has had its say
+fn resolve_targets<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
+    local_folder_path: &Path,
+    folder_name: &str
+) -> (Vec<DeployServer>, Vec<String>) {
+    let mut servers = config.servers.clone();
+    let mut post_commands = config.post_commands.clone();
+
+    // Let the build folder override targets/commands via its own descriptor
+    if config.respect_folder_descriptor {
+        match read_folder_descriptor(local_folder_path) {
+            Ok(Some(descriptor)) => {
+                emit_log(app_handle, format!("Using {} from {}", FOLDER_DESCRIPTOR_NAME, folder_name), LogLevel::Info);
+                if let Some(ids) = &descriptor.servers {
+                    servers.retain(|s| ids.contains(&s.id));
+                    for id in ids {
+                        if !servers.iter().any(|s| &s.id == id) {
+                            emit_log(app_handle, format!("{} references unknown server id {}", FOLDER_DESCRIPTOR_NAME, id), LogLevel::Warn);
+                        }
+                    }
+                }
+                if let Some(remote_path) = &descriptor.remote_path {
+                    for server in servers.iter_mut() {
+                        server.remote_path = remote_path.clone();
+                    }
+                }
+                if let Some(commands) = descriptor.post_commands {
+                    post_commands = commands;
+                }
+            },
+            Ok(None) => {},
+            Err(e) => {
+                emit_log(app_handle, format!("Ignoring folder descriptor: {}", e), LogLevel::Warn);
+            }
+        }
+    }
+    (servers, post_commands)
 }
 
 pub fn deploy_to_remote<R: tauri::Runtime>(
@@ -93,20 +714,37 @@ pub fn deploy_to_remote<R: tauri::Runtime>(
     }
 
     if config.servers.is_empty() {
-        emit_log(app_handle, "Deployment enabled but no servers configured.".to_string(), "warn");
+        emit_log(app_handle, "Deployment enabled but no servers configured.".to_string(), LogLevel::Warn);
         return Ok(());
     }
 
-    emit_log(app_handle, format!("Starting deployment for {} servers...", config.servers.len()), "info");
+    let (servers, post_commands) = resolve_targets(app_handle, config, local_folder_path, folder_name);
+    if servers.is_empty() {
+        emit_log(app_handle, "No servers left to deploy to after applying folder descriptor.".to_string(), LogLevel::Warn);
+        return Ok(());
+    }
+
+    emit_log(app_handle, format!("Starting deployment for {} servers...", servers.len()), LogLevel::Info);
 
-    let servers = config.servers.clone();
     let local_path_buf = local_folder_path.to_path_buf();
     let folder_name_owned = folder_name.to_string();
     let app_handle = app_handle.clone();
-    let post_commands = config.post_commands.clone();
+    let controls = DeployControls::attach(&app_handle, &should_cancel, &is_paused);
+    let (should_cancel, is_paused) = (controls.cancel.clone(), controls.paused.clone());
 
     // Calculate total size once for progress reporting
-    let total_size = calculate_size(&local_path_buf);
+    let total_size = match calculate_size(&app_handle, &local_path_buf, config.follow_symlinks, &should_cancel) {
+        Ok(size) => size,
+        Err(e) => {
+            emit_log(&app_handle, format!("Deployment skipped: {}", e), LogLevel::Warn);
+            return Ok(());
+        }
+    };
+
+    let started = Instant::now();
+    let mut deployed_servers = Vec::new();
+    let mut errors = Vec::new();
+    let mut failed_ids = Vec::new();
 
     // Deploy sequentially to avoid UI progress conflicts and ensure stability
     let server_count = servers.len();
@@ -124,49 +762,85 @@ pub fn deploy_to_remote<R: tauri::Runtime>(
         
         // Check cancel before starting next server
         if cancel.load(Ordering::SeqCst) {
-            emit_log(&app_handle, "Remaining deployments cancelled.".to_string(), "warn");
+            emit_log(&app_handle, "Remaining deployments cancelled.".to_string(), LogLevel::Warn);
             break;
         }
 
-        emit_log(&app_handle, format!("Deploying to server {}/{} [{}]", idx + 1, server_count, server.name), "info");
+        if skipped_for_run(&app_handle, &server.id) {
+            emit_log(&app_handle, format!("[{}] Skipped for this run", server.name), LogLevel::Warn);
+            continue;
+        }
+
+        emit_log(&app_handle, format!("Deploying to server {}/{} [{}]", idx + 1, server_count, server.name), LogLevel::Info);
 
         // Run synchronously in the current thread (which is already a background task)
-        if let Err(e) = deploy_single_server(&handle, &server, &local, &name, &commands, total_size, cancel, pause) {
-             emit_log(&handle, format!("[{}] Deployment failed: {}", server.name, e), "error");
+        let outcome = deploy_single_server(&handle, config, &server, &local, &name, &commands, total_size, cancel, pause);
+        record_deploy_history(&handle, &server, &name, &local, &remote_target_for(&server, &name), &outcome);
+        if let Err(e) = outcome {
+             emit_log(&handle, format!("[{}] Deployment failed: {}", server.name, e), LogLevel::Error);
+             errors.push(format!("[{}] {}", server.name, ui_message(&handle, e)));
+             failed_ids.push(server.id.clone());
              // Continue to next server even if one fails
         } else {
-             emit_log(&handle, format!("[{}] Deployment successful", server.name), "success");
+             emit_log(&handle, format!("[{}] Deployment successful", server.name), LogLevel::Success);
+             deployed_servers.push(server.name.clone());
         }
     }
 
+    save_failed_servers(&app_handle, &local_path_buf, failed_ids);
+
+    let success = errors.is_empty();
+    webhook::notify(config, success, serde_json::json!({
+        "event": "deploy",
+        "success": success,
+        "folder": folder_name,
+        "servers": deployed_servers,
+        "errors": errors,
+        "duration_ms": started.elapsed().as_millis() as u64,
+    }));
+
     Ok(())
 }
 
-fn substitute_variables(cmd: &str, folder_name: &str, local_path: &Path) -> String {
+// Collects `.tar.gz` files up to `max_depth` directories below `dir` (0 = top level only)
+fn collect_archives(dir: &Path, depth: usize, max_depth: usize, found: &mut Vec<std::path::PathBuf>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if depth < max_depth {
+                    collect_archives(&path, depth + 1, max_depth, found);
+                }
+            } else if path.to_string_lossy().ends_with(".tar.gz") {
+                found.push(path);
+            }
+        }
+    }
+}
+
+fn substitute_variables(cmd: &str, folder_name: &str, local_path: &Path, config: &AppConfig) -> String {
     let mut result = cmd.to_string();
     
     // Resolve ${filename} dynamically by scanning for .tar.gz files
     if result.contains("${filename}") {
-        let replacement = if let Ok(entries) = fs::read_dir(local_path) {
-            let mut found_name = folder_name.to_string();
-            // First, try to find a tar.gz file. We take the first one we find.
-            // Logic: Scan directory, if we see ANY .tar.gz, we use it.
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.is_file() {
-                    if let Some(name) = path.file_name() {
-                        let name_str = name.to_string_lossy();
-                        if name_str.ends_with(".tar.gz") {
-                             // Found a tar.gz file. Use its stem.
-                            found_name = name_str.trim_end_matches(".tar.gz").to_string();
-                            break; 
-                        }
-                    }
-                }
-            }
-            found_name
+        let mut archives = Vec::new();
+        collect_archives(local_path, 0, config.filename_search_depth, &mut archives);
+
+        // Pick deterministically: newest mtime (ties by name) or alphabetically-first file name
+        let file_name_of = |p: &std::path::PathBuf| p.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let picked = if config.filename_pick == "newest" {
+            archives.iter().max_by(|a, b| {
+                let ma = fs::metadata(a).and_then(|m| m.modified()).ok();
+                let mb = fs::metadata(b).and_then(|m| m.modified()).ok();
+                ma.cmp(&mb).then_with(|| file_name_of(b).cmp(&file_name_of(a)))
+            })
         } else {
-            folder_name.to_string()
+            archives.iter().min_by_key(|p| file_name_of(p))
+        };
+
+        let replacement = match picked {
+            Some(path) => file_name_of(path).trim_end_matches(".tar.gz").to_string(),
+            None => folder_name.to_string(),
         };
         
         result = result.replace("${filename}", &replacement);
@@ -175,8 +849,200 @@ fn substitute_variables(cmd: &str, folder_name: &str, local_path: &Path) -> Stri
     result
 }
 
+/// Name of the deployed folder under `server.remote_path`.
+///
+/// An empty template keeps the local folder name. Otherwise `${folder}`, `${version}` (the
+/// "(...)" part of the folder name, or the whole name when absent) and `${date}` (deploy day,
+/// YYYYMMDD) are substituted; a template without tokens is a fixed name such as `current`.
+/// With a fixed name every deploy overwrites the same remote folder, so `backup_before_deploy`
+/// keeps `<name>.bak` and `rollback_deploy` must be pointed at that same fixed path.
+fn remote_folder_name(server: &DeployServer, folder_name: &str) -> String {
+    let template = server.remote_folder_name_template.trim();
+    if template.is_empty() {
+        return folder_name.to_string();
+    }
+    let version = folder_name
+        .rfind('(')
+        .filter(|_| folder_name.ends_with(')'))
+        .map(|start| &folder_name[start + 1..folder_name.len() - 1])
+        .unwrap_or(folder_name);
+    template
+        .replace("${folder}", folder_name)
+        .replace("${version}", version)
+        .replace("${date}", &chrono::Local::now().format("%Y%m%d").to_string())
+}
+
+// A bare extension like "tar.gz" or ".tar.gz" means "*.tar.gz"; anything else is a glob
+fn artifact_glob(pattern: &str) -> Result<glob::Pattern, String> {
+    let has_glob = pattern.contains(['*', '?', '[']);
+    let pattern = if has_glob {
+        pattern.to_string()
+    } else {
+        format!("*.{}", pattern.trim_start_matches('.'))
+    };
+    glob::Pattern::new(&pattern).map_err(|e| format!("Invalid artifact pattern '{}': {}", pattern, e))
+}
+
+// Newest (by mtime) file anywhere under `dir` whose name matches
+fn find_newest_artifact(dir: &Path, pattern: &glob::Pattern) -> Option<std::path::PathBuf> {
+    let mut newest: Option<(std::time::SystemTime, std::path::PathBuf)> = None;
+    let mut dirs_to_visit = vec![dir.to_path_buf()];
+    while let Some(current) = dirs_to_visit.pop() {
+        let Ok(entries) = fs::read_dir(&current) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                dirs_to_visit.push(path);
+            } else if pattern.matches(&entry.file_name().to_string_lossy()) {
+                let mtime = entry.metadata().and_then(|m| m.modified()).unwrap_or(std::time::UNIX_EPOCH);
+                if newest.as_ref().map_or(true, |(t, _)| mtime > *t) {
+                    newest = Some((mtime, path));
+                }
+            }
+        }
+    }
+    newest.map(|(_, path)| path)
+}
+
+// deploy_single_artifact: upload one file to `server.remote_path` instead of the whole tree.
+// `progress` starts out describing the local folder and is narrowed to the artifact found in it.
+fn upload_single_artifact<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    server: &DeployServer,
+    sftp: &ssh2::Sftp,
+    pattern: &str,
+    progress: &mut UploadContext,
+    should_cancel: &Arc<AtomicBool>,
+    is_paused: &Arc<AtomicBool>
+) -> Result<(), String> {
+    let local_folder_path = PathBuf::from(&progress.local_path_str);
+    let sess = progress.session.clone();
+    let glob = artifact_glob(pattern)?;
+    let artifact = find_newest_artifact(&local_folder_path, &glob)
+        .ok_or_else(|| format!("No file matching '{}' found in {}", pattern, local_folder_path.display()))?;
+    let file_name = artifact.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let remote_dir = server.remote_path.trim_end_matches('/');
+    let remote_file = format!("{}/{}", remote_dir, file_name);
+
+    emit_log(app_handle, format!("[{}] Uploading artifact {} to {}", server.name, file_name, remote_file), LogLevel::Info);
+    create_remote_dir(&sess, remote_dir)?;
+
+    progress.total_size = fs::metadata(&artifact).map_err(|e| e.to_string())?.len();
+    progress.local_path_str = artifact.to_string_lossy().to_string();
+    progress.remote_path_display = format!("[{}] {}:{}", server.name, server.host, remote_file);
+    upload_with_progress(app_handle, &sess, sftp, &artifact, Path::new(&remote_file), progress, should_cancel, is_paused)
+}
+
+// Looks for leftovers such as lock/pid files from a crashed deploy that would otherwise
+// break post commands, and removes them when remote_precheck_remove is on.
+fn precheck_remote_paths<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
+    server: &DeployServer,
+    sftp: &ssh2::Sftp
+) {
+    for path in &config.remote_precheck_paths {
+        let stat = match sftp.stat(Path::new(path)) {
+            Ok(stat) => stat,
+            Err(_) => continue,
+        };
+        let age = stat.mtime
+            .and_then(|mtime| std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH).ok()
+                .map(|now| now.as_secs().saturating_sub(mtime)))
+            .map(|secs| format!("{}s old", secs))
+            .unwrap_or_else(|| "unknown age".to_string());
+        emit_log(app_handle, format!("[{}] Found {} ({} bytes, {})", server.name, path, stat.size.unwrap_or(0), age), LogLevel::Warn);
+
+        if !config.remote_precheck_remove {
+            continue;
+        }
+        if stat.is_dir() {
+            emit_log(app_handle, format!("[{}] Not removing {}: it is a directory", server.name, path), LogLevel::Warn);
+            continue;
+        }
+        match sftp.unlink(Path::new(path)) {
+            Ok(_) => emit_log(app_handle, format!("[{}] Removed {}", server.name, path), LogLevel::Info),
+            Err(e) => emit_log(app_handle, format!("[{}] Failed to remove {}: {}", server.name, path, e), LogLevel::Error),
+        }
+    }
+}
+
+/// Deletes everything under `remote_root` that has no counterpart under `local_root`, so a
+/// redeploy doesn't leave the previous build's files behind. Refuses to run against an empty
+/// local folder, which would otherwise wipe the whole remote target.
+fn mirror_delete_extras<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    sftp: &ssh2::Sftp,
+    local_root: &Path,
+    remote_root: &str,
+    server_name: &str
+) -> Result<usize, String> {
+    let remote_root = remote_root.trim_end_matches('/');
+    if remote_root.is_empty() {
+        return Err("Mirror delete refused: remote target is the filesystem root".to_string());
+    }
+    let local_has_files = fs::read_dir(local_root).map(|mut entries| entries.next().is_some()).unwrap_or(false);
+    if !local_has_files {
+        return Err(format!("Mirror delete refused: local folder {} is empty or unreadable", local_root.display()));
+    }
+
+    let mut deleted = 0;
+    let mut dirs_to_visit = vec![String::new()];
+    while let Some(rel_dir) = dirs_to_visit.pop() {
+        let remote_dir = if rel_dir.is_empty() { remote_root.to_string() } else { format!("{}/{}", remote_root, rel_dir) };
+        let entries = sftp.readdir(Path::new(&remote_dir)).map_err(|e| format!("Failed to list {}: {}", remote_dir, e))?;
+        for (path, stat) in entries {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let rel = if rel_dir.is_empty() { name } else { format!("{}/{}", rel_dir, name) };
+            let local = local_root.join(&rel);
+            let remote = format!("{}/{}", remote_root, rel);
+            if stat.is_dir() {
+                if local.is_dir() {
+                    dirs_to_visit.push(rel);
+                } else {
+                    deleted += remove_remote_tree(app_handle, sftp, &remote, server_name)?;
+                }
+            } else if !local.is_file() {
+                sftp.unlink(Path::new(&remote)).map_err(|e| format!("Failed to delete {}: {}", remote, e))?;
+                emit_log(app_handle, format!("[{}] Mirror: deleted {}", server_name, remote), LogLevel::Info);
+                deleted += 1;
+            }
+        }
+    }
+    Ok(deleted)
+}
+
+// Depth-first delete of a remote directory, logging each file; returns how many files went
+fn remove_remote_tree<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, sftp: &ssh2::Sftp, remote_dir: &str, server_name: &str) -> Result<usize, String> {
+    let mut deleted = 0;
+    for (path, stat) in sftp.readdir(Path::new(remote_dir)).map_err(|e| format!("Failed to list {}: {}", remote_dir, e))? {
+        let remote = path.to_string_lossy().replace('\\', "/");
+        if stat.is_dir() {
+            deleted += remove_remote_tree(app_handle, sftp, &remote, server_name)?;
+        } else {
+            sftp.unlink(&path).map_err(|e| format!("Failed to delete {}: {}", remote, e))?;
+            emit_log(app_handle, format!("[{}] Mirror: deleted {}", server_name, remote), LogLevel::Info);
+            deleted += 1;
+        }
+    }
+    sftp.rmdir(Path::new(remote_dir)).map_err(|e| format!("Failed to remove directory {}: {}", remote_dir, e))?;
+    emit_log(app_handle, format!("[{}] Mirror: removed directory {}", server_name, remote_dir), LogLevel::Info);
+    Ok(deleted)
+}
+
+const UPLOAD_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+fn sleep_unless_cancelled(duration: Duration, should_cancel: &AtomicBool) {
+    let deadline = Instant::now() + duration;
+    while Instant::now() < deadline && !should_cancel.load(Ordering::SeqCst) {
+        std::thread::sleep(Duration::from_millis(100).min(deadline - Instant::now()));
+    }
+}
+
 fn deploy_single_server<R: tauri::Runtime>(
     app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
     server: &DeployServer,
     local_folder_path: &Path,
     folder_name: &str,
@@ -184,127 +1050,620 @@ fn deploy_single_server<R: tauri::Runtime>(
     total_size: u64,
     should_cancel: Arc<AtomicBool>,
     is_paused: Arc<AtomicBool>
-) -> Result<(), String> {
-    emit_log(app_handle, format!("[{}] Connecting to {}:{}", server.name, server.host, server.remote_path), "info");
+) -> Result<Vec<CommandResult>, String> {
+    crate::config::validate_remote_path(server)?;
+    emit_log(app_handle, format!("[{}] Connecting to {}:{}", server.name, server.host, server.remote_path), LogLevel::Info);
 
     // 1. Connect
-    let tcp = TcpStream::connect(format!("{}:{}", server.host, server.port))
-        .map_err(|e| e.to_string())?;
-    let mut sess = Session::new().unwrap();
-    sess.set_tcp_stream(tcp);
-    sess.handshake().map_err(|e| e.to_string())?;
-    sess.userauth_password(&server.user, &server.password).map_err(|e| e.to_string())?;
+    let (mut sess, mut permit) = open_session(app_handle, server, &should_cancel)?;
 
-    emit_log(app_handle, format!("[{}] Connected", server.name), "info");
+    emit_log(app_handle, format!("[{}] Connected", server.name), LogLevel::Info);
 
-    // 2. Create remote directory
-    let remote_target = format!("{}/{}", server.remote_path.trim_end_matches('/'), folder_name);
-    
-    let sftp = sess.sftp().map_err(|e| format!("SFTP init failed: {}", e))?;
+    let mut sftp = sess.sftp().map_err(|e| format!("SFTP init failed: {}", e))?;
+
+    precheck_remote_paths(app_handle, config, server, &sftp);
+
+    let artifact_pattern = server.deploy_single_artifact.as_deref().map(str::trim).filter(|p| !p.is_empty());
+    if let Some(pattern) = artifact_pattern {
+        // 2. Only push the newest matching artifact into remote_path
+        let server_display = format!("[{}] {}:{}", server.name, server.host, server.remote_path);
+        let mut progress = UploadContext::new(app_handle, &sess, 0, &local_folder_path.to_string_lossy(), &server_display, config);
+        upload_single_artifact(app_handle, server, &sftp, pattern, &mut progress, &should_cancel, &is_paused)?;
+    } else {
+        // 2. Create remote directory
+        let remote_target = remote_target_for(server, folder_name);
+        let upload_target = upload_target_for(server, folder_name);
+        let staged = upload_target != remote_target;
+        let checkpoint = DeployCheckpoint::open(app_handle, server, &upload_target);
+        if checkpoint.is_resuming() {
+            emit_log(app_handle, format!("[{}] Resuming interrupted deploy: {} files already uploaded", server.name, checkpoint.done.len()), LogLevel::Info);
+        }
     
-    // Check if exists logic...
-    // Always force upload or check logic? The original code checked existence.
-    // For auto-deploy, we usually want to overwrite or ensure it's there.
+        // Check if exists logic...
+        // Always force upload or check logic? The original code checked existence.
+        // For auto-deploy, we usually want to overwrite or ensure it's there.
     
-    // Check if exists
-    let should_upload = match sftp.stat(Path::new(&remote_target)) {
-        Ok(_) => {
-             emit_log(app_handle, format!("[{}] Remote directory {} already exists. Continuing upload/overwrite.", server.name, remote_target), "info");
-             true
-        },
-        Err(_) => {
-             emit_log(app_handle, format!("[{}] Uploading to {}", server.name, remote_target), "info");
-             
-             let mut channel = sess.channel_session().unwrap();
-             channel.exec(&format!("mkdir -p {}", remote_target)).unwrap();
-             channel.send_eof().unwrap();
-             let mut s = String::new();
-             channel.read_to_string(&mut s).unwrap();
-             channel.wait_close().unwrap();
-             true
+        // Check if exists
+        let should_upload = match sftp.stat(Path::new(&upload_target)) {
+            Ok(_) => {
+                 emit_log(app_handle, format!("[{}] Remote directory {} already exists. Continuing upload/overwrite.", server.name, upload_target), LogLevel::Info);
+                 // A resumed upload must not be moved into a backup half-way through; staged
+                 // deploys back up the live folder when they replace it
+                 if config.backup_before_deploy && !checkpoint.is_resuming() && !staged {
+                     let backup = backup_remote_folder(&sess, &sftp, &remote_target, folder_name)?;
+                     emit_log(app_handle, format!("[{}] Previous release backed up to {}", server.name, backup), LogLevel::Info);
+                 }
+                 true
+            },
+            Err(_) => {
+                 emit_log(app_handle, format!("[{}] Uploading to {}", server.name, upload_target), LogLevel::Info);
+                 create_remote_dir(&sess, &upload_target)?;
+                 true
+            }
+        };
+
+        if should_upload && config.tar_upload {
+             let server_display = format!("[{}] {}:{}", server.name, server.host, upload_target);
+             let mut progress = UploadContext::new(app_handle, &sess, 0, &local_folder_path.to_string_lossy(), &server_display, config);
+             upload_as_archive(app_handle, config, &sftp, &upload_target, &mut progress, &should_cancel, &is_paused)?;
+        } else if should_upload {
+             let local_path_str = local_folder_path.to_string_lossy();
+             let server_display = format!("[{}] {}:{}", server.name, server.host, upload_target);
+             let mut progress = UploadContext::new(app_handle, &sess, total_size, &local_path_str, &server_display, config);
+             progress.checkpoint = Some(checkpoint);
+
+             let mut attempt = 0;
+             loop {
+                 match upload_with_progress(
+                    app_handle, 
+                    &sess,
+                    &sftp, 
+                    local_folder_path, 
+                    Path::new(&upload_target),
+                    &mut progress,
+                    &should_cancel,
+                    &is_paused
+                 ) {
+                     Ok(()) => break,
+                     Err(e) if attempt < config.upload_retries && !should_cancel.load(Ordering::SeqCst) => {
+                         let delay = UPLOAD_RETRY_BASE_DELAY * 2u32.pow(attempt.min(16));
+                         attempt += 1;
+                         emit_log(app_handle, format!("[{}] Upload failed ({}), retry {}/{} in {}ms", server.name, e, attempt, config.upload_retries, delay.as_millis()), LogLevel::Warn);
+                         sleep_unless_cancelled(delay, &should_cancel);
+                         if should_cancel.load(Ordering::SeqCst) {
+                             return Err("Deployment cancelled".to_string());
+                         }
+                         // A dropped connection leaves the session unusable. The checkpoint skips the
+                         // files already uploaded, so only the failed file starts over.
+                         drop(permit);
+                         let (new_sess, new_permit) = open_session(app_handle, server, &should_cancel)?;
+                         sftp = new_sess.sftp().map_err(|e| format!("SFTP init failed: {}", e))?;
+                         sess = new_sess;
+                         progress.session = sess.clone();
+                         permit = new_permit;
+                         progress.copied_bytes = 0;
+                     }
+                     Err(e) => return Err(e),
+                 }
+             }
+             if let Some(checkpoint) = progress.checkpoint.take() {
+                 checkpoint.finish();
+             }
         }
-    };
 
-    if should_upload {
-         let mut copied_bytes = 0;
-         let start_time = Instant::now();
-         let mut last_emit_time = Instant::now();
-         let local_path_str = local_folder_path.to_string_lossy();
-         let server_display = format!("[{}] {}:{}", server.name, server.host, remote_target);
+        if should_upload && config.mirror_delete {
+            let deleted = mirror_delete_extras(app_handle, &sftp, local_folder_path, &upload_target, &server.name)?;
+            emit_log(app_handle, format!("[{}] Mirror: deleted {} remote file(s) not present locally", server.name, deleted), LogLevel::Info);
+        }
 
-         upload_with_progress(
-            app_handle, 
-            &sftp, 
-            local_folder_path, 
-            Path::new(&remote_target),
-            total_size,
-            &mut copied_bytes,
-            start_time,
-            &mut last_emit_time,
-            &local_path_str,
-            &server_display,
-            &should_cancel,
-            &is_paused
-         )?;
+        if staged {
+            if let Some(backup) = move_into_place(&sess, &sftp, &upload_target, &remote_target, folder_name, config.backup_before_deploy)? {
+                emit_log(app_handle, format!("[{}] Previous release backed up to {}", server.name, backup), LogLevel::Info);
+            }
+            emit_log(app_handle, format!("[{}] Moved staged upload {} to {}", server.name, upload_target, remote_target), LogLevel::Info);
+        }
     }
 
     // 3. Exec commands
     if !post_commands.is_empty() {
-        emit_log(app_handle, format!("[{}] Executing post commands...", server.name), "info");
-        
-        for cmd in post_commands {
-            if should_cancel.load(Ordering::SeqCst) {
-                 return Err("Cancelled".to_string());
+        emit_log(app_handle, format!("[{}] Executing post commands...", server.name), LogLevel::Info);
+        let commands: Vec<String> = post_commands.iter()
+            .map(|cmd| substitute_variables(cmd, folder_name, local_folder_path, config))
+            .collect();
+        return run_post_commands(app_handle, &sess, &format!("[{}] ", server.name), &commands, config, &should_cancel);
+    }
+
+    Ok(vec![])
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+pub struct CommandResult {
+    pub command: String, // After variable substitution
+    pub output: String, // stdout and stderr merged, capped at max_command_output_bytes
+    pub exit_code: i32,
+}
+
+/// Adds a DEPLOY_COMPLETED / DEPLOY_FAILED history entry for one server, keeping what each
+/// post command returned so remote changes can be audited later.
+pub fn record_deploy_history<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    server: &DeployServer,
+    folder_name: &str,
+    local_path: &Path,
+    remote_target: &str,
+    outcome: &Result<Vec<CommandResult>, String>
+) {
+    crate::stats::record(app_handle, |s| match outcome {
+        Ok(_) => s.total_deploys += 1,
+        Err(_) => s.total_errors += 1,
+    });
+    let (action_type, description, command_results) = match outcome {
+        Ok(results) => ("DEPLOY_COMPLETED", format!("Deployed {} to {}", folder_name, server.name), results.clone()),
+        Err(e) => ("DEPLOY_FAILED", format!("Deploy of {} to {} failed: {}", folder_name, server.name, ui_message(app_handle, e.clone())), vec![]),
+    };
+    add_history_entry(app_handle, HistoryEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: chrono::Local::now().to_rfc3339(),
+        action_type: action_type.to_string(),
+        description,
+        folder_name: folder_name.to_string(),
+        source_path: local_path.to_string_lossy().to_string(),
+        target_path: format!("{}:{}", server.host, remote_target),
+        copied_files_count: 0,
+        total_size: 0,
+        files: vec![],
+        run_id: current_run_id(app_handle),
+        command_results,
+    });
+}
+
+// Runs the (already substituted) commands one after another; a non-zero exit is logged and reported but doesn't stop the rest
+fn run_post_commands<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    sess: &Session,
+    log_prefix: &str,
+    commands: &[String],
+    config: &AppConfig,
+    should_cancel: &Arc<AtomicBool>
+) -> Result<Vec<CommandResult>, String> {
+    let flush_every = Duration::from_millis(config.command_output_flush_ms);
+    let mut results = vec![];
+    for final_cmd in commands {
+        if should_cancel.load(Ordering::SeqCst) {
+            return Err("Deployment cancelled".to_string());
+        }
+
+        emit_log(app_handle, format!("{}$ {}", log_prefix, final_cmd), LogLevel::Info);
+        let (output, exit_code) = run_post_command(sess, final_cmd, config.max_command_output_bytes, flush_every, &mut |batch| {
+            if !batch.trim().is_empty() {
+                emit_log(app_handle, format!("{}> {}", log_prefix, batch.trim_end()), LogLevel::Info);
             }
+        })?;
 
-            let final_cmd = substitute_variables(cmd, folder_name, local_folder_path);
-            emit_log(app_handle, format!("[{}] $ {}", server.name, final_cmd), "info");
-            
-            let mut channel = sess.channel_session().map_err(|e| e.to_string())?;
-            channel.exec(&final_cmd).map_err(|e| e.to_string())?;
-            channel.send_eof().map_err(|e| e.to_string())?;
-            
-            let mut s = String::new();
-            channel.read_to_string(&mut s).map_err(|e| e.to_string())?;
-            channel.wait_close().unwrap();
-            
-            if !s.is_empty() {
-                emit_log(app_handle, format!("[{}] > {}", server.name, s.trim()), "info");
+        if exit_code != 0 {
+            emit_log(app_handle, format!("{}Command failed (exit {})", log_prefix, exit_code), LogLevel::Error);
+        }
+        results.push(CommandResult { command: final_cmd.clone(), output, exit_code });
+    }
+    Ok(results)
+}
+
+/// Runs the configured post commands on `server` again, without uploading anything.
+pub fn rerun_post_commands<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
+    server: &DeployServer,
+    folder_name: &str,
+    local_path: &str
+) -> Result<Vec<CommandResult>, String> {
+    let never_cancel = Arc::new(AtomicBool::new(false));
+    let (sess, _permit) = open_session(app_handle, server, &never_cancel)?;
+
+    emit_log(app_handle, format!("[{}] Re-running post commands for {}", server.name, folder_name), LogLevel::Info);
+    let commands: Vec<String> = config.post_commands.iter()
+        .map(|cmd| substitute_variables(cmd, folder_name, Path::new(local_path), config))
+        .collect();
+    run_post_commands(app_handle, &sess, &format!("[{}] ", server.name), &commands, config, &never_cancel)
+}
+
+// Packs the folder contents (not the folder itself) into a gzipped tarball.
+// Returns the size of the tar stream before compression.
+// Walks with the same SymlinkGuard rules as the file-by-file upload, so the archive holds
+// exactly what an SFTP upload of the folder would
+fn create_local_archive<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    local_folder_path: &Path,
+    archive_path: &Path,
+    follow_symlinks: bool
+) -> Result<u64, String> {
+    let file = fs::File::create(archive_path).map_err(|e| format!("Failed to create {}: {}", archive_path.display(), e))?;
+    let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    let mut builder = tar::Builder::new(CountingWriter { inner: encoder, bytes: 0 });
+    builder.follow_symlinks(follow_symlinks);
+    let pack_error = |path: &Path, e: std::io::Error| format!("Failed to pack {}: {}", path.display(), e);
+    let mut links = SymlinkGuard::new(local_folder_path, follow_symlinks);
+    let mut pending = vec![(local_folder_path.to_path_buf(), PathBuf::from("."))];
+    while let Some((dir, archive_dir)) = pending.pop() {
+        builder.append_dir(&archive_dir, &dir).map_err(|e| pack_error(&dir, e))?;
+        let entries = fs::read_dir(&dir).map_err(|e| pack_error(&dir, e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let entry_name = archive_dir.join(entry.file_name());
+            let Ok(file_type) = entry.file_type() else { continue };
+            match links.classify(&path, file_type) {
+                WalkEntry::Dir => pending.push((path, entry_name)),
+                WalkEntry::File => builder.append_path_with_name(&path, &entry_name).map_err(|e| pack_error(&path, e))?,
+                WalkEntry::Link => emit_log(app_handle, format!("Skipped symlink {}", path.display()), LogLevel::Info),
+                WalkEntry::Cycle => emit_log(app_handle, format!("Skipped {}: it links back to a folder already packed", path.display()), LogLevel::Warn),
             }
-            
-            if channel.exit_status().unwrap() != 0 {
-                emit_log(app_handle, format!("[{}] Command failed (exit {})", server.name, channel.exit_status().unwrap()), "error");
+        }
+    }
+    let counted = builder.into_inner().map_err(|e| format!("Failed to finish archive: {}", e))?;
+    counted.inner.finish().map_err(|e| format!("Failed to finish archive: {}", e))?;
+    Ok(counted.bytes)
+}
+
+struct CountingWriter<W: Write> {
+    inner: W,
+    bytes: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// tar_upload: tar -> upload one archive -> extract into `remote_target` -> clean up both sides.
+/// Progress is reported on the archive bytes; `progress` starts out describing the local folder.
+fn upload_as_archive<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
+    sftp: &ssh2::Sftp,
+    remote_target: &str,
+    progress: &mut UploadContext,
+    should_cancel: &Arc<AtomicBool>,
+    is_paused: &Arc<AtomicBool>
+) -> Result<(), String> {
+    let local_folder_path = PathBuf::from(&progress.local_path_str);
+    let sess = progress.session.clone();
+    let folder_name = local_folder_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let archive_name = format!("{}-{}.tar.gz", folder_name, uuid::Uuid::new_v4());
+    let local_archive = std::env::temp_dir().join(&archive_name);
+    let remote_archive = format!("{}/{}", remote_target.trim_end_matches('/'), archive_name);
+
+    emit_log(app_handle, format!("Packing {} into {}", local_folder_path.display(), local_archive.display()), LogLevel::Info);
+    let result = (|| {
+        let packed_size = create_local_archive(app_handle, &local_folder_path, &local_archive, config.follow_symlinks)?;
+        let archive_size = fs::metadata(&local_archive).map_err(|e| e.to_string())?.len();
+        let ratio = if packed_size > 0 { archive_size as f64 / packed_size as f64 * 100.0 } else { 100.0 };
+        emit_log(app_handle, format!("Compressed {} bytes to {} bytes ({:.1}% of original)", packed_size, archive_size, ratio), LogLevel::Info);
+
+        progress.total_size = archive_size;
+        progress.local_path_str = local_archive.to_string_lossy().to_string();
+        upload_with_progress(app_handle, &sess, sftp, &local_archive, Path::new(&remote_archive), progress, should_cancel, is_paused)?;
+
+        emit_log(app_handle, format!("Extracting {} on remote", archive_name), LogLevel::Info);
+        let cmd = format!("tar xzf {a} -C {t} && rm -f {a}", a = shell_quote(&remote_archive), t = shell_quote(remote_target));
+        let (output, status) = run_remote_command(&sess, &cmd)?;
+        if status != 0 {
+            return Err(format!("Remote extract failed (exit {}): {}", status, output.trim()));
+        }
+        Ok(())
+    })();
+
+    let _ = fs::remove_file(&local_archive);
+    result
+}
+
+pub const DEPLOY_MANIFEST_NAME: &str = ".deploy-manifest.json";
+
+/// Written into the backup folder so `rollback_deploy` knows what it can restore.
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
+struct DeployManifest {
+    folder_name: String,
+    target: String,
+    backup: String,
+    created_at: String,
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+// Runs a command and returns (output, exit status)
+fn run_remote_command(sess: &Session, cmd: &str) -> Result<(String, i32), String> {
+    let mut channel = sess.channel_session().map_err(|e| e.to_string())?;
+    channel.exec(cmd).map_err(|e| e.to_string())?;
+    channel.send_eof().map_err(|e| e.to_string())?;
+    let mut output = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = read_with_keepalive(sess, &mut channel, &mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        output.extend_from_slice(&buf[..n]);
+    }
+    let s = String::from_utf8(output).map_err(|e| e.to_string())?;
+    channel.wait_close().map_err(|e| e.to_string())?;
+    let status = channel.exit_status().map_err(|e| e.to_string())?;
+    Ok((s, status))
+}
+
+// libssh2 only sends keepalives when asked, so a blocking read wakes up every interval to send
+// one; a command that runs silently for minutes would otherwise let a NAT or firewall drop the session
+fn read_with_keepalive(sess: &Session, channel: &mut ssh2::Channel, buf: &mut [u8]) -> std::io::Result<usize> {
+    sess.set_timeout(KEEPALIVE_INTERVAL_SECS * 1000);
+    let result = loop {
+        match channel.read(buf) {
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                let _ = sess.keepalive_send();
             }
+            other => break other,
         }
+    };
+    sess.set_timeout(0);
+    result
+}
+
+// Like run_remote_command, but stderr is folded into the output and only the first `max_bytes`
+// are kept (0 = no limit). The rest is still drained so the command can finish and report its exit status.
+// Kept output is handed to `on_output` as it arrives, in whole-line batches at most every `flush_every`;
+// whatever is left is flushed when the command ends.
+fn run_post_command(
+    sess: &Session,
+    cmd: &str,
+    max_bytes: u64,
+    flush_every: Duration,
+    on_output: &mut dyn FnMut(&str)
+) -> Result<(String, i32), String> {
+    let mut channel = sess.channel_session().map_err(|e| e.to_string())?;
+    channel.handle_extended_data(ssh2::ExtendedData::Merge).map_err(|e| e.to_string())?;
+    channel.exec(cmd).map_err(|e| e.to_string())?;
+    channel.send_eof().map_err(|e| e.to_string())?;
+
+    let limit = if max_bytes == 0 { u64::MAX } else { max_bytes };
+    let mut captured = Vec::new();
+    let mut flushed = 0;
+    let mut last_flush = Instant::now();
+    let mut dropped = 0u64;
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = read_with_keepalive(sess, &mut channel, &mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        let keep = (limit - captured.len() as u64).min(n as u64) as usize;
+        captured.extend_from_slice(&buf[..keep]);
+        dropped += (n - keep) as u64;
+
+        if last_flush.elapsed() >= flush_every {
+            if let Some(end) = captured[flushed..].iter().rposition(|&b| b == b'\n') {
+                on_output(&String::from_utf8_lossy(&captured[flushed..flushed + end + 1]));
+                flushed += end + 1;
+                last_flush = Instant::now();
+            }
+        }
+    }
+    if flushed < captured.len() {
+        on_output(&String::from_utf8_lossy(&captured[flushed..]));
+    }
+
+    channel.wait_close().map_err(|e| e.to_string())?;
+    let status = channel.exit_status().map_err(|e| e.to_string())?;
+    let mut output = String::from_utf8_lossy(&captured).into_owned();
+    if dropped > 0 {
+        output.push_str(&format!("\n(output truncated, {} more bytes)", dropped));
     }
+    Ok((output, status))
+}
 
+// mkdir -p that reports failures (e.g. permission denied) instead of panicking
+fn create_remote_dir(sess: &Session, remote_dir: &str) -> Result<(), String> {
+    let (output, status) = run_remote_command(sess, &format!("mkdir -p {} 2>&1", shell_quote(remote_dir)))
+        .map_err(|e| format!("Failed to create remote directory {}: {}", remote_dir, e))?;
+    if status != 0 {
+        let detail = output.trim();
+        let hint = if detail.contains("Permission denied") {
+            " Check that the SSH user can write to the parent directory."
+        } else {
+            ""
+        };
+        return Err(format!("Failed to create remote directory {} (exit {}): {}.{}", remote_dir, status, detail, hint));
+    }
     Ok(())
 }
 
-fn calculate_size(path: &Path) -> u64 {
-    let mut size = 0;
-    if path.is_dir() {
-        if let Ok(entries) = fs::read_dir(path) {
-            for entry in entries.flatten() {
-                size += calculate_size(&entry.path());
+// Replaces the live folder with the finished upload from the staging dir. The swap itself is a
+// single `mv`, so on the same filesystem the live path is never half-written. Returns the backup
+// taken of the previous release, if any.
+fn move_into_place(
+    sess: &Session,
+    sftp: &ssh2::Sftp,
+    staged: &str,
+    remote_target: &str,
+    folder_name: &str,
+    backup_first: bool
+) -> Result<Option<String>, String> {
+    let mut backup = None;
+    if sftp.stat(Path::new(remote_target)).is_ok() {
+        if backup_first {
+            backup = Some(backup_remote_folder(sess, sftp, remote_target, folder_name)?);
+        }
+        let (output, status) = run_remote_command(sess, &format!("rm -rf {} 2>&1", shell_quote(remote_target)))?;
+        if status != 0 {
+            return Err(format!("Failed to remove previous {} (exit {}): {}", remote_target, status, output.trim()));
+        }
+    } else if let Some(parent) = Path::new(remote_target).parent() {
+        create_remote_dir(sess, &parent.to_string_lossy())?;
+    }
+
+    let (output, status) = run_remote_command(sess, &format!("mv {} {} 2>&1", shell_quote(staged), shell_quote(remote_target)))?;
+    if status != 0 {
+        return Err(format!("Failed to move {} to {} (exit {}): {}", staged, remote_target, status, output.trim()));
+    }
+    Ok(backup)
+}
+
+fn backup_path_for(remote_target: &str) -> String {
+    format!("{}.bak", remote_target.trim_end_matches('/'))
+}
+
+// Snapshot the current release to `<target>.bak` before it gets overwritten
+fn backup_remote_folder(sess: &Session, sftp: &ssh2::Sftp, remote_target: &str, folder_name: &str) -> Result<String, String> {
+    let backup = backup_path_for(remote_target);
+    let cmd = format!("rm -rf {b} && cp -a {t} {b}", b = shell_quote(&backup), t = shell_quote(remote_target));
+    let (output, status) = run_remote_command(sess, &cmd)?;
+    if status != 0 {
+        return Err(format!("Backup of {} failed (exit {}): {}", remote_target, status, output.trim()));
+    }
+
+    let manifest = DeployManifest {
+        folder_name: folder_name.to_string(),
+        target: remote_target.to_string(),
+        backup: backup.clone(),
+        created_at: chrono::Local::now().to_rfc3339(),
+    };
+    let content = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    let manifest_path = format!("{}/{}", backup, DEPLOY_MANIFEST_NAME);
+    let mut file = sftp.create(Path::new(&manifest_path)).map_err(|e| format!("Failed to write {}: {}", manifest_path, e))?;
+    file.write_all(content.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(backup)
+}
+
+/// Removes a remote directory, returning whether it existed.
+pub fn remove_remote_dir<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, server: &DeployServer, remote_path: &str) -> Result<bool, String> {
+    let (sess, _permit) = open_session(app_handle, server, &AtomicBool::new(false))?;
+
+    let cmd = format!("if [ -d {p} ]; then rm -rf {p} && echo removed; fi", p = shell_quote(remote_path));
+    let (output, status) = run_remote_command(&sess, &cmd)?;
+    if status != 0 {
+        return Err(format!("Failed to remove {} (exit {}): {}", remote_path, status, output.trim()));
+    }
+    Ok(output.contains("removed"))
+}
+
+/// Restores `<remote_path>.bak` over `remote_path`, then runs `rollback_commands`.
+///
+/// Prerequisite: the previous deploy ran with `backup_before_deploy` enabled, which leaves
+/// a `.deploy-manifest.json` inside the backup. Without it there is nothing to restore and
+/// this returns an error without touching the server. The backup is consumed by the restore.
+pub fn rollback_deploy<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    server: &DeployServer,
+    remote_path: &str,
+    rollback_commands: &[String]
+) -> Result<(), String> {
+    let target = remote_path.trim_end_matches('/').to_string();
+    let backup = backup_path_for(&target);
+    emit_log(app_handle, format!("[{}] Rolling back {} from {}", server.name, target, backup), LogLevel::Info);
+
+    let (sess, _permit) = open_session(app_handle, server, &AtomicBool::new(false))?;
+
+    let sftp = sess.sftp().map_err(|e| format!("SFTP init failed: {}", e))?;
+    let manifest_path = format!("{}/{}", backup, DEPLOY_MANIFEST_NAME);
+    let mut manifest_file = sftp.open(Path::new(&manifest_path))
+        .map_err(|_| format!("No backup found for {} (missing {}). Rollback requires a previous deploy with backup_before_deploy enabled.", target, manifest_path))?;
+    let mut content = String::new();
+    manifest_file.read_to_string(&mut content).map_err(|e| e.to_string())?;
+    let manifest: DeployManifest = serde_json::from_str(&content)
+        .map_err(|e| format!("Invalid {}: {}", DEPLOY_MANIFEST_NAME, e))?;
+    emit_log(app_handle, format!("[{}] Found backup of {} taken at {}", server.name, manifest.folder_name, manifest.created_at), LogLevel::Info);
+
+    let cmd = format!(
+        "rm -rf {t} && mv {b} {t} && rm -f {m}",
+        t = shell_quote(&target),
+        b = shell_quote(&backup),
+        m = shell_quote(&format!("{}/{}", target, DEPLOY_MANIFEST_NAME))
+    );
+    let (output, status) = run_remote_command(&sess, &cmd)?;
+    if status != 0 {
+        return Err(format!("Restore failed (exit {}): {}", status, output.trim()));
+    }
+    emit_log(app_handle, format!("[{}] Restored {}", server.name, target), LogLevel::Success);
+
+    for cmd in rollback_commands {
+        emit_log(app_handle, format!("[{}] $ {}", server.name, cmd), LogLevel::Info);
+        let (output, status) = run_remote_command(&sess, cmd)?;
+        if !output.is_empty() {
+            emit_log(app_handle, format!("[{}] > {}", server.name, output.trim()), LogLevel::Info);
+        }
+        if status != 0 {
+            emit_log(app_handle, format!("[{}] Command failed (exit {})", server.name, status), LogLevel::Error);
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, serde::Serialize, Clone)]
+struct SizeProgressEvent {
+    path: String,
+    files: u64,
+    bytes: u64,
+    done: bool,
+}
+
+// Walks `path` summing file sizes. Large trees take a while, so a "size-progress" event goes
+// out every 200ms and the walk stops as soon as should_cancel is set.
+fn calculate_size<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    path: &Path,
+    follow_symlinks: bool,
+    should_cancel: &Arc<AtomicBool>
+) -> Result<u64, String> {
+    let mut event = SizeProgressEvent {
+        path: ui_message(app_handle, path.to_string_lossy().to_string()),
+        files: 0,
+        bytes: 0,
+        done: false,
+    };
+    let mut last_emit = Instant::now();
+    // Links the upload won't follow don't count towards its size
+    let mut links = SymlinkGuard::new(path, follow_symlinks);
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        if should_cancel.load(Ordering::SeqCst) {
+            return Err("Deployment cancelled".to_string());
+        }
+        if current.is_dir() {
+            if let Ok(entries) = fs::read_dir(&current) {
+                pending.extend(entries.flatten()
+                    .filter(|e| e.file_type().is_ok_and(|t| matches!(links.classify(&e.path(), t), WalkEntry::Dir | WalkEntry::File)))
+                    .map(|e| e.path()));
             }
+        } else if let Ok(meta) = fs::metadata(&current) {
+            event.files += 1;
+            event.bytes += meta.len();
+        }
+        if last_emit.elapsed().as_millis() > 200 {
+            last_emit = Instant::now();
+            let _ = app_handle.emit("size-progress", event.clone());
         }
-    } else if let Ok(meta) = fs::metadata(path) {
-        size = meta.len();
     }
-    size
+    event.done = true;
+    let _ = app_handle.emit("size-progress", event.clone());
+    Ok(event.bytes)
 }
 
 pub fn deploy_manual<R: tauri::Runtime>(
     app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
     server: &DeployServer,
     post_commands: &[String],
     local_path: &str,
     remote_path: &str,
     should_cancel: Arc<AtomicBool>,
     is_paused: Arc<AtomicBool>
-) -> Result<(), String> {
-    emit_log(app_handle, format!("Starting manual deployment: {} -> [{}] {}:{}", local_path, server.name, server.host, remote_path), "info");
+) -> Result<Vec<CommandResult>, String> {
+    emit_log(app_handle, format!("Starting manual deployment: {} -> [{}] {}:{}", local_path, server.name, server.host, remote_path), LogLevel::Info);
 
     let local_p = Path::new(local_path);
     if !local_p.exists() {
@@ -312,19 +1671,14 @@ pub fn deploy_manual<R: tauri::Runtime>(
     }
 
     // Calculate total size for progress
-    emit_log(app_handle, "Calculating size...".to_string(), "info");
-    let total_size = calculate_size(local_p);
-    emit_log(app_handle, format!("Total size: {} bytes", total_size), "info");
+    emit_log(app_handle, "Calculating size...".to_string(), LogLevel::Info);
+    let total_size = calculate_size(app_handle, local_p, config.follow_symlinks, &should_cancel)?;
+    emit_log(app_handle, format!("Total size: {} bytes", total_size), LogLevel::Info);
 
     // 1. Connect
-    let tcp = TcpStream::connect(format!("{}:{}", server.host, server.port))
-        .map_err(|e| e.to_string())?;
-    let mut sess = Session::new().unwrap();
-    sess.set_tcp_stream(tcp);
-    sess.handshake().map_err(|e| e.to_string())?;
-    sess.userauth_password(&server.user, &server.password).map_err(|e| e.to_string())?;
+    let (sess, _permit) = open_session(app_handle, server, &should_cancel)?;
 
-    emit_log(app_handle, "SSH Connected & Authenticated".to_string(), "success");
+    emit_log(app_handle, "SSH Connected & Authenticated".to_string(), LogLevel::Success);
 
     let sftp = sess.sftp().map_err(|e| format!("SFTP init failed: {}", e))?;
 
@@ -339,77 +1693,51 @@ pub fn deploy_manual<R: tauri::Runtime>(
     let target_path_str = target_path_str.replace("\\", "/");
     let target_p = Path::new(&target_path_str);
 
-    emit_log(app_handle, format!("Uploading to {}", target_path_str), "info");
+    emit_log(app_handle, format!("Uploading to {}", target_path_str), LogLevel::Info);
 
     if let Some(parent) = target_p.parent() {
         let parent_str = parent.to_string_lossy().replace("\\", "/");
         if !parent_str.is_empty() {
-            let mut channel = sess.channel_session().unwrap();
-            channel.exec(&format!("mkdir -p {}", parent_str)).unwrap();
-            channel.send_eof().unwrap();
-            let mut s = String::new();
-            channel.read_to_string(&mut s).unwrap();
-            channel.wait_close().unwrap();
+            create_remote_dir(&sess, &parent_str)?;
         }
     }
 
     // Upload with progress
-    let mut copied_bytes = 0;
     let start_time = Instant::now();
-    let mut last_emit_time = Instant::now();
     
     // Initial emit
     let server_display = format!("{}:{}/{}", server.host, server.remote_path.trim_end_matches('/'), target_path_str.split('/').last().unwrap_or_default());
     emit_progress(app_handle, &local_p.file_name().unwrap_or_default().to_string_lossy(), 0, total_size, 0, 0, 0, local_path, &server_display);
 
+    // Manual deploys are interactive, so progress is always shown
+    let mut progress = UploadContext::new(app_handle, &sess, total_size, local_path, &server_display, config);
+    progress.quiet = false;
     upload_with_progress(
         app_handle, 
+        &sess,
         &sftp, 
         local_p, 
         target_p, 
-        total_size, 
-        &mut copied_bytes, 
-        start_time, 
-        &mut last_emit_time,
-        local_path,
-        &server_display,
+        &mut progress,
         &should_cancel,
         &is_paused
     )?;
     
-    emit_log(app_handle, "Upload complete".to_string(), "success");
+    emit_log(app_handle, "Upload complete".to_string(), LogLevel::Success);
     // Emit 100%
     emit_progress(app_handle, &local_p.file_name().unwrap_or_default().to_string_lossy(), total_size, total_size, 0, 0, start_time.elapsed().as_secs(), local_path, &server_display);
 
     // Exec commands
     if !post_commands.is_empty() {
-        emit_log(app_handle, "Executing post-deployment commands...".to_string(), "info");
+        emit_log(app_handle, "Executing post-deployment commands...".to_string(), LogLevel::Info);
         let folder_name = local_p.file_name().unwrap_or_default().to_string_lossy();
-        
-        for cmd in post_commands {
-            if should_cancel.load(Ordering::SeqCst) {
-                return Err("Deployment cancelled".to_string());
-            }
-            
-            let final_cmd = substitute_variables(cmd, &folder_name, local_p);
-             emit_log(app_handle, format!("$ {}", final_cmd), "info");
-            let mut channel = sess.channel_session().map_err(|e| e.to_string())?;
-            channel.exec(&final_cmd).map_err(|e| e.to_string())?;
-            channel.send_eof().map_err(|e| e.to_string())?;
-            
-            let mut s = String::new();
-            channel.read_to_string(&mut s).map_err(|e| e.to_string())?;
-            channel.wait_close().unwrap();
-            if !s.is_empty() {
-                emit_log(app_handle, format!("> {}", s.trim()), "info");
-            }
-            if channel.exit_status().unwrap() != 0 {
-                emit_log(app_handle, format!("Command failed with exit code {}", channel.exit_status().unwrap()), "error");
-            }
-        }
+        let commands: Vec<String> = post_commands.iter()
+            .map(|cmd| substitute_variables(cmd, &folder_name, local_p, config))
+            .collect();
+        return run_post_commands(app_handle, &sess, "", &commands, config, &should_cancel);
     }
 
-    Ok(())
+    Ok(vec![])
 }
 
 fn upload_recursive<R: tauri::Runtime>(
@@ -441,15 +1769,11 @@ fn upload_recursive<R: tauri::Runtime>(
 
 fn upload_with_progress<R: tauri::Runtime>(
     app_handle: &tauri::AppHandle<R>,
+    sess: &Session,
     sftp: &ssh2::Sftp,
     local_path: &Path,
     remote_path: &Path,
-    total_size: u64,
-    copied_bytes: &mut u64,
-    start_time: Instant,
-    last_emit_time: &mut Instant,
-    local_path_str: &str,
-    remote_path_display: &str,
+    progress: &mut UploadContext,
     should_cancel: &Arc<AtomicBool>,
     is_paused: &Arc<AtomicBool>
 ) -> Result<(), String> {
@@ -462,67 +1786,175 @@ fn upload_with_progress<R: tauri::Runtime>(
         for entry in fs::read_dir(local_path).map_err(|e| e.to_string())? {
             let entry = entry.map_err(|e| e.to_string())?;
             let path = entry.path();
+            let file_type = entry.file_type().map_err(|e| e.to_string())?;
+            match progress.links.classify(&path, file_type) {
+                WalkEntry::Link => {
+                    emit_log(app_handle, format!("Skipped symlink {}", path.display()), LogLevel::Info);
+                    continue;
+                }
+                WalkEntry::Cycle => {
+                    emit_log(app_handle, format!("Skipped {}: it links back to a folder already uploaded", path.display()), LogLevel::Warn);
+                    continue;
+                }
+                WalkEntry::Dir | WalkEntry::File => {}
+            }
             let name = entry.file_name();
             let remote_parent_str = remote_path.to_string_lossy().to_string().replace("\\", "/");
             let child_name_str = name.to_string_lossy();
             let remote_child_str = format!("{}/{}", remote_parent_str.trim_end_matches('/'), child_name_str);
             let remote_child_path = Path::new(&remote_child_str);
             
-            upload_with_progress(app_handle, sftp, &path, remote_child_path, total_size, copied_bytes, start_time, last_emit_time, local_path_str, remote_path_display, should_cancel, is_paused)?;
+            upload_with_progress(app_handle, sess, sftp, &path, remote_child_path, progress, should_cancel, is_paused)?;
         }
     } else {
-        let mut local_file = fs::File::open(local_path).map_err(|e| e.to_string())?;
-        let mut remote_file = sftp.create(remote_path).map_err(|e| e.to_string())?;
-        
-        let mut buffer = [0u8; 64 * 1024]; // 64KB buffer
-        loop {
-            // Check cancel
-            if should_cancel.load(Ordering::SeqCst) {
-                return Err("Deployment cancelled".to_string());
+        let remote_str = remote_path.to_string_lossy().replace("\\", "/");
+        let metadata = fs::metadata(local_path).map_err(|e| e.to_string())?;
+        let mtime = mtime_secs(&metadata);
+        // Already uploaded by an interrupted run; the size check catches remote folders cleaned up since
+        if let Some(checkpoint) = &progress.checkpoint {
+            if checkpoint.is_done(&remote_str, metadata.len(), mtime)
+                && sftp.stat(remote_path).ok().and_then(|s| s.size) == Some(metadata.len()) {
+                progress.copied_bytes += metadata.len();
+                progress.report(app_handle, &local_path.file_name().unwrap_or_default().to_string_lossy(), false);
+                return Ok(());
             }
-            
-            // Check pause
-            while is_paused.load(Ordering::SeqCst) {
-                if should_cancel.load(Ordering::SeqCst) {
-                    return Err("Deployment cancelled".to_string());
+        }
+
+        let copied_before = progress.copied_bytes;
+        let local_hash = match stream_file(app_handle, sftp, local_path, remote_path, progress, should_cancel, is_paused) {
+            Ok(hash) => hash,
+            // Awkward servers (odd permissions, picky SFTP implementations) sometimes accept the plain upload
+            Err(e) if !should_cancel.load(Ordering::SeqCst) => {
+                emit_log(app_handle, format!("Upload of {} failed ({}), retrying with the simple upload path", local_path.display(), e), LogLevel::Warn);
+                upload_recursive(app_handle, sftp, local_path, remote_path)
+                    .map_err(|fallback_err| format!("{} (simple upload also failed: {})", e, fallback_err))?;
+                progress.copied_bytes = copied_before + metadata.len();
+                progress.report(app_handle, &local_path.file_name().unwrap_or_default().to_string_lossy(), false);
+                emit_log(app_handle, format!("Uploaded {} via the simple upload path", local_path.display()), LogLevel::Info);
+                match &progress.verify_command {
+                    Some(cmd) => Some(hash_local_file(cmd, local_path)?),
+                    None => None,
                 }
-                std::thread::sleep(std::time::Duration::from_millis(100));
             }
+            Err(e) => return Err(e),
+        };
 
-            let n = local_file.read(&mut buffer).map_err(|e| e.to_string())?;
-            if n == 0 { break; }
-            remote_file.write_all(&buffer[..n]).map_err(|e| e.to_string())?;
-            
-            *copied_bytes += n as u64;
-            
-            let now = Instant::now();
-            if now.duration_since(*last_emit_time).as_millis() > 200 {
-                let elapsed = start_time.elapsed().as_secs_f64();
-                let speed = if elapsed > 0.0 {
-                    (*copied_bytes as f64 / elapsed) as u64
-                } else {
-                    0
-                };
-                let eta = if speed > 0 && total_size > *copied_bytes {
-                    (total_size - *copied_bytes) / speed
-                } else {
-                    0
-                };
-                
-                emit_progress(
-                    app_handle, 
-                    &local_path.file_name().unwrap_or_default().to_string_lossy(),
-                    *copied_bytes, 
-                    total_size, 
-                    speed, 
-                    eta,
-                    elapsed as u64,
-                    local_path_str,
-                    remote_path_display
-                );
-                *last_emit_time = now;
+        if let (Some(hash), Some(cmd)) = (local_hash, &progress.verify_command) {
+            verify_remote_hash(sess, cmd, &remote_str, &hash)?;
+        }
+        if let Some(checkpoint) = progress.checkpoint.as_mut() {
+            checkpoint.record(&remote_str, metadata.len(), mtime);
+        }
+    }
+    Ok(())
+}
+
+// Copies one file with progress, pause and cancel; returns the local hash when verify_upload is on
+fn stream_file<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    sftp: &ssh2::Sftp,
+    local_path: &Path,
+    remote_path: &Path,
+    progress: &mut UploadContext,
+    should_cancel: &Arc<AtomicBool>,
+    is_paused: &Arc<AtomicBool>
+) -> Result<Option<String>, String> {
+    let mut local_file = fs::File::open(local_path).map_err(|e| e.to_string())?;
+    let mut remote_file = sftp.create(remote_path).map_err(|e| e.to_string())?;
+    let mut hasher = match &progress.verify_command {
+        Some(cmd) => Some(FileHasher::for_command(cmd)?),
+        None => None,
+    };
+    
+    let mut buffer = [0u8; 64 * 1024]; // 64KB buffer
+    loop {
+        // Check cancel
+        if should_cancel.load(Ordering::SeqCst) {
+            return Err("Deployment cancelled".to_string());
+        }
+        
+        // Check pause; nothing else is sent meanwhile, so keep the session alive
+        while is_paused.load(Ordering::SeqCst) {
+            if should_cancel.load(Ordering::SeqCst) {
+                return Err("Deployment cancelled".to_string());
             }
+            let _ = progress.session.keepalive_send();
+            std::thread::sleep(std::time::Duration::from_millis(100));
         }
+
+        let n = local_file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if n == 0 { break; }
+        remote_file.write_all(&buffer[..n]).map_err(|e| e.to_string())?;
+        if let Some(h) = hasher.as_mut() {
+            h.update(&buffer[..n]);
+        }
+        
+        progress.copied_bytes += n as u64;
+        progress.throttle.consume(n as u64, should_cancel, is_paused);
+        progress.report(app_handle, &local_path.file_name().unwrap_or_default().to_string_lossy(), false);
+    }
+    // Make sure the remote side has the whole file before hashing it
+    drop(remote_file);
+    Ok(hasher.map(FileHasher::finalize_hex))
+}
+
+fn hash_local_file(cmd: &str, local_path: &Path) -> Result<String, String> {
+    let mut hasher = FileHasher::for_command(cmd)?;
+    let mut local_file = fs::File::open(local_path).map_err(|e| e.to_string())?;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let n = local_file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if n == 0 { break; }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher.finalize_hex())
+}
+
+/// Local counterpart of the configured remote hash command.
+enum FileHasher {
+    Sha256(sha2::Sha256),
+    Md5(md5::Md5),
+}
+
+impl FileHasher {
+    fn for_command(cmd: &str) -> Result<Self, String> {
+        use sha2::Digest;
+        if cmd.contains("sha256") {
+            Ok(FileHasher::Sha256(sha2::Sha256::new()))
+        } else if cmd.contains("md5") {
+            Ok(FileHasher::Md5(md5::Md5::new()))
+        } else {
+            Err(format!("Unsupported remote hash command '{}': expected a sha256sum or md5sum variant", cmd))
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        use sha2::Digest;
+        match self {
+            FileHasher::Sha256(h) => h.update(data),
+            FileHasher::Md5(h) => h.update(data),
+        }
+    }
+
+    fn finalize_hex(self) -> String {
+        use sha2::Digest;
+        let bytes = match self {
+            FileHasher::Sha256(h) => h.finalize().to_vec(),
+            FileHasher::Md5(h) => h.finalize().to_vec(),
+        };
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+// Runs `<cmd> <file>` remotely and compares the first token of its output with the local hash
+fn verify_remote_hash(sess: &Session, cmd: &str, remote_file: &str, local_hash: &str) -> Result<(), String> {
+    let (output, status) = run_remote_command(sess, &format!("{} {}", cmd, shell_quote(remote_file)))?;
+    if status != 0 {
+        return Err(format!("Remote hash command failed for {} (exit {}): {}", remote_file, status, output.trim()));
+    }
+    let remote_hash = output.split_whitespace().next().unwrap_or_default().to_lowercase();
+    if remote_hash != local_hash {
+        return Err(format!("Checksum mismatch for {}: local {} remote {}", remote_file, local_hash, remote_hash));
     }
     Ok(())
 }
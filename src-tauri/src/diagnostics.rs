@@ -0,0 +1,94 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use tauri::State;
+use zip::write::SimpleFileOptions;
+
+use crate::config::{self, redact_secrets, AppConfig};
+use crate::history::get_history_path;
+use crate::logging::redact_paths;
+use crate::AppState;
+
+// Only the tail of the log goes into the bundle; older lines rarely help and can be huge
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Zips the redacted config, the recent log, history and a system summary into `path`
+/// for attaching to support tickets.
+#[tauri::command]
+pub fn export_diagnostics(app_handle: tauri::AppHandle, state: State<AppState>, path: String) -> Result<(), String> {
+    let config = state.config_snapshot();
+
+    let config_json = serde_json::to_string_pretty(&redact_secrets(&config)).map_err(|e| e.to_string())?;
+    let log = read_log_tail(&config::get_log_path(&app_handle)).unwrap_or_default();
+    let history = fs::read_to_string(get_history_path(&app_handle)).unwrap_or_default();
+    let system_info = system_info(&app_handle, &config);
+
+    let mut entries = vec![
+        ("config.json", config_json),
+        ("app.log", log),
+        ("history.jsonl", history),
+        ("system_info.txt", system_info),
+    ];
+
+    // Secrets can still end up in logs or history (e.g. typed into a post command),
+    // so every file is checked against the real values before it is written
+    let secrets = collect_secrets(&config);
+    for (_, content) in entries.iter_mut() {
+        for secret in &secrets {
+            if content.contains(secret.as_str()) {
+                *content = content.replace(secret.as_str(), "********");
+            }
+        }
+        if config.redact_paths {
+            *content = redact_paths(content);
+        }
+    }
+
+    let file = fs::File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in entries {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(content.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+
+    log::info!("Exported diagnostics to {}", path);
+    Ok(())
+}
+
+fn read_log_tail(path: &std::path::Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(MAX_LOG_BYTES)))?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf).to_string())
+}
+
+fn collect_secrets(config: &AppConfig) -> Vec<String> {
+    let mut secrets = vec![config.ssh_password.clone()];
+    secrets.extend(config.servers.iter().map(|s| s.password.clone()));
+    secrets.extend(config.servers.iter().filter_map(|s| s.passphrase.clone()));
+    secrets.extend(config.webhook_url.clone());
+    // Very short values would mask unrelated text all over the bundle
+    secrets.retain(|s| s.len() >= 3);
+    secrets
+}
+
+fn system_info(app_handle: &tauri::AppHandle, config: &AppConfig) -> String {
+    let free_disk = match fs2::available_space(&config.local_path) {
+        Ok(bytes) => format!("{} bytes", bytes),
+        Err(e) => format!("unknown ({})", e),
+    };
+    let data_dir = config::app_data_dir(app_handle).to_string_lossy().to_string();
+    format!(
+        "App version: {}\nOS: {} ({})\nData dir: {}\nLocal path: {}\nFree disk on local path: {}\nGenerated: {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        data_dir,
+        config.local_path,
+        free_disk,
+        chrono::Local::now().to_rfc3339()
+    )
+}
@@ -0,0 +1,22 @@
+use std::fmt;
+
+/// Outcome of a folder copy task that didn't fully succeed.
+#[derive(Debug)]
+pub enum SyncError {
+    /// Stopped by the user; not reported as a failure.
+    Cancelled,
+    /// Reading the source or writing the destination failed.
+    Io(String),
+    /// The copy itself completed but deploying it failed.
+    Deploy(String),
+}
+
+impl fmt::Display for SyncError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncError::Cancelled => write!(f, "Cancelled by user"),
+            SyncError::Io(e) => write!(f, "{}", e),
+            SyncError::Deploy(e) => write!(f, "Deployment failed: {}", e),
+        }
+    }
+}
@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::PathBuf;
-use tauri::Manager;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
 use chrono::Local;
+use tauri::Manager;
+
+use crate::AppState;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HistoryEntry {
@@ -22,6 +27,10 @@ pub struct HistoryEntry {
     pub copied_files_count: usize,
     pub total_size: u64,
     pub files: Vec<String>, 
+    #[serde(default)]
+    pub run_id: String, // Scan run that produced the entry, empty for system events
+    #[serde(default)]
+    pub command_results: Vec<crate::deploy::CommandResult>, // Post commands of a deploy entry
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -42,55 +51,200 @@ pub fn add_system_event(app_handle: tauri::AppHandle, action: String, desc: Stri
         copied_files_count: 0,
         total_size: 0,
         files: vec![],
+        run_id: String::new(),
+        command_results: vec![],
     };
     add_history_entry(&app_handle, entry);
 }
 
+// Scans, deploys and the UI record history from different threads. Every read-modify-write of
+// the file holds this, so a compaction can't drop a line appended while it rewrote the file.
+// The helpers below it (load/append/save/migrate) expect the caller to hold it. It guards the
+// number of lines in history.jsonl, counted on the first append and kept up to date after that,
+// so deciding when to compact doesn't mean reading the whole file on every event.
+static HISTORY_LOCK: Mutex<Option<usize>> = Mutex::new(None);
+
+fn lock_history() -> MutexGuard<'static, Option<usize>> {
+    HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 pub fn add_history_entry<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, entry: HistoryEntry) {
-    let mut store = load_history(app_handle);
-    // Prepend
-    store.entries.insert(0, entry);
-    // Keep max 100 entries
-    if store.entries.len() > 100 {
-        store.entries.truncate(100);
+    let mut lines = lock_history();
+    append_and_compact(app_handle, &mut lines, &entry);
+}
+
+fn append_and_compact<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, lines: &mut Option<usize>, entry: &HistoryEntry) {
+    append_history(app_handle, entry);
+    let count = match *lines {
+        Some(count) => count + 1,
+        None => count_lines(&get_history_path(app_handle)),
+    };
+
+    // The file only ever grows on append; rewrite it down to the limit once it holds twice that
+    let limit = history_limit(app_handle);
+    if limit > 0 && count > limit * 2 {
+        let store = load_history(app_handle);
+        save_history(app_handle, &store);
+        *lines = Some(store.entries.len());
+    } else {
+        *lines = Some(count);
     }
-    save_history(app_handle, &store);
+}
+
+/// One page of history, newest first.
+#[derive(Debug, Serialize, Clone)]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    pub total: usize,
 }
 
 #[tauri::command]
-pub fn get_history(app_handle: tauri::AppHandle) -> HistoryStore {
-    load_history(&app_handle)
+pub fn get_history(app_handle: tauri::AppHandle, offset: Option<usize>, limit: Option<usize>) -> HistoryPage {
+    let store = {
+        let _guard = lock_history();
+        load_history(&app_handle)
+    };
+    let total = store.entries.len();
+    let entries = store.entries.into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect();
+    HistoryPage { entries, total }
 }
 
 #[tauri::command]
 pub fn clear_history(app_handle: tauri::AppHandle) -> Result<(), String> {
-    let path = get_history_path(&app_handle);
-    if path.exists() {
-        fs::remove_file(path).map_err(|e| e.to_string())?;
+    let mut lines = lock_history();
+    // Recounted on the next append, whatever part of this fails
+    *lines = None;
+    for path in [get_history_path(&app_handle), legacy_history_path(&app_handle)] {
+        if path.exists() {
+            fs::remove_file(path).map_err(|e| e.to_string())?;
+        }
     }
     Ok(())
 }
 
-fn get_history_path<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> PathBuf {
-    app_handle.path().app_data_dir().unwrap().join("history.json")
+// One JSON entry per line, oldest first, so recording an event is a single append
+pub fn get_history_path<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> PathBuf {
+    crate::config::app_data_dir(app_handle).join("history.jsonl")
 }
 
-pub fn load_history<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> HistoryStore {
-    let path = get_history_path(app_handle);
-    if path.exists() {
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(store) = serde_json::from_str(&content) {
-                return store;
+// Single pretty-printed document, newest first; converted on first load
+fn legacy_history_path<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> PathBuf {
+    crate::config::app_data_dir(app_handle).join("history.json")
+}
+
+fn history_limit<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> usize {
+    app_handle
+        .try_state::<AppState>()
+        .map(|state| state.read_config(|c| c.history_limit))
+        .unwrap_or(crate::config::AppConfig::default().history_limit)
+}
+
+fn count_lines(path: &Path) -> usize {
+    fs::read(path).map(|bytes| bytes.iter().filter(|b| **b == b'\n').count()).unwrap_or(0)
+}
+
+/// Newest first, at most `history_limit` entries (0 = all).
+fn load_history<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> HistoryStore {
+    migrate_legacy_history(app_handle);
+
+    let mut entries: Vec<HistoryEntry> = vec![];
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    if let Ok(content) = fs::read_to_string(get_history_path(app_handle)) {
+        // A line cut short by a crash mid-append is skipped rather than losing the whole history
+        for entry in content.lines().filter_map(|line| serde_json::from_str::<HistoryEntry>(line).ok()) {
+            match positions.get(&entry.id) {
+                Some(&pos) => entries[pos] = entry,
+                None => {
+                    positions.insert(entry.id.clone(), entries.len());
+                    entries.push(entry);
+                }
             }
         }
     }
-    HistoryStore::default()
+    entries.reverse();
+
+    let limit = history_limit(app_handle);
+    if limit > 0 {
+        entries.truncate(limit);
+    }
+    HistoryStore { entries }
 }
 
-pub fn save_history<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, store: &HistoryStore) {
+fn append_history<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, entry: &HistoryEntry) {
+    migrate_legacy_history(app_handle);
     let path = get_history_path(app_handle);
     if let Some(parent) = path.parent() {
         let _ = fs::create_dir_all(parent);
     }
-    let _ = fs::write(path, serde_json::to_string_pretty(store).unwrap_or_default());
+    let Ok(line) = serde_json::to_string(entry) else { return };
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// Rewrites the whole file from `store` (newest first), e.g. to compact it.
+fn save_history<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, store: &HistoryStore) {
+    let path = get_history_path(app_handle);
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let content: String = store.entries.iter().rev()
+        .filter_map(|entry| serde_json::to_string(entry).ok())
+        .map(|line| line + "\n")
+        .collect();
+    let _ = fs::write(path, content);
+}
+
+fn migrate_legacy_history<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) {
+    let legacy = legacy_history_path(app_handle);
+    if !legacy.exists() || get_history_path(app_handle).exists() {
+        return;
+    }
+    let store: HistoryStore = fs::read_to_string(&legacy)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    save_history(app_handle, &store);
+    let _ = fs::remove_file(&legacy);
+    log::info!("Converted {} history entries to {}", store.entries.len(), get_history_path(app_handle).display());
+}
+
+// Kept apart from history.jsonl so it survives history truncation and "Clear History"
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ScanState {
+    #[serde(default)]
+    pub last_successful_scan: Option<String>, // RFC 3339, last scan that copied at least one folder
+}
+
+fn get_scan_state_path<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> PathBuf {
+    crate::config::app_data_dir(app_handle).join("scan_state.json")
+}
+
+pub fn load_scan_state<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> ScanState {
+    fs::read_to_string(get_scan_state_path(app_handle))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn record_successful_scan<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) {
+    // The self test copies a dummy build, which says nothing about the real shares
+    if app_handle.try_state::<AppState>().is_some_and(|s| s.is_self_testing.load(std::sync::atomic::Ordering::SeqCst)) {
+        return;
+    }
+    let mut state = load_scan_state(app_handle);
+    state.last_successful_scan = Some(Local::now().to_rfc3339());
+    let path = get_scan_state_path(app_handle);
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(path, serde_json::to_string_pretty(&state).unwrap_or_default());
+}
+
+#[tauri::command]
+pub fn get_last_successful_scan(app_handle: tauri::AppHandle) -> Option<String> {
+    load_scan_state(&app_handle).last_successful_scan
 }
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+use crate::config::AppConfig;
+use crate::AppState;
+
+/// Tunables that running transfers re-read on every chunk, so changing them
+/// (via `set_runtime_limits` or a config save) takes effect without restarting the scan.
+#[derive(Debug, Default)]
+pub struct RuntimeLimits {
+    pub max_bytes_per_sec: AtomicU64, // 0 = unlimited
+    pub max_concurrency: AtomicUsize,
+}
+
+impl RuntimeLimits {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let limits = Self::default();
+        limits.apply(config.max_bytes_per_sec, config.max_concurrency);
+        limits
+    }
+
+    pub fn apply(&self, max_bytes_per_sec: u64, max_concurrency: usize) {
+        self.max_bytes_per_sec.store(max_bytes_per_sec, Ordering::SeqCst);
+        self.max_concurrency.store(max_concurrency.max(1), Ordering::SeqCst);
+    }
+}
+
+/// The app-wide limits, or a snapshot of `config` when there is no app state (e.g. early startup).
+pub fn live_limits<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, config: &AppConfig) -> Arc<RuntimeLimits> {
+    app_handle
+        .try_state::<AppState>()
+        .map(|state| state.limits.clone())
+        .unwrap_or_else(|| Arc::new(RuntimeLimits::from_config(config)))
+}
+
+/// Bounds how much work runs at once by `max_concurrency`. Unlike a semaphore sized at the
+/// start, it re-reads the limit while waiting, so a change made mid-scan applies to the work
+/// that hasn't started yet.
+#[derive(Clone)]
+pub struct ConcurrencyGate {
+    limits: Arc<RuntimeLimits>,
+    active: Arc<AtomicUsize>,
+}
+
+impl ConcurrencyGate {
+    pub fn new(limits: Arc<RuntimeLimits>) -> Self {
+        Self { limits, active: Arc::new(AtomicUsize::new(0)) }
+    }
+
+    /// Waits for a free slot; the slot is returned when the permit is dropped.
+    pub async fn enter(&self) -> GatePermit {
+        loop {
+            let active = self.active.load(Ordering::SeqCst);
+            let limit = self.limits.max_concurrency.load(Ordering::SeqCst).max(1);
+            if active < limit
+                && self.active.compare_exchange(active, active + 1, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+                return GatePermit { active: self.active.clone() };
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
+
+pub struct GatePermit {
+    active: Arc<AtomicUsize>,
+}
+
+impl Drop for GatePermit {
+    fn drop(&mut self) {
+        self.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// Budget is tracked per one-second window so a lowered limit bites within a second
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Sleeps just enough to keep a transfer under `max_bytes_per_sec`.
+pub struct Throttle {
+    limits: Arc<RuntimeLimits>,
+    window_start: Instant,
+    window_bytes: u64,
+}
+
+impl Throttle {
+    pub fn new(limits: Arc<RuntimeLimits>) -> Self {
+        Self { limits, window_start: Instant::now(), window_bytes: 0 }
+    }
+
+    pub fn consume(&mut self, bytes: u64, should_cancel: &AtomicBool, is_paused: &AtomicBool) {
+        if self.window_start.elapsed() >= WINDOW {
+            self.window_start = Instant::now();
+            self.window_bytes = 0;
+        }
+        self.window_bytes += bytes;
+
+        let limit = self.limits.max_bytes_per_sec.load(Ordering::SeqCst);
+        if limit == 0 {
+            return;
+        }
+        let due = Duration::from_secs_f64(self.window_bytes as f64 / limit as f64);
+        // Sleep in short steps so cancel and pause stay responsive; the caller's pause loop takes
+        // over from there, and the window has expired by the time it resumes
+        while self.window_start.elapsed() < due
+            && !should_cancel.load(Ordering::SeqCst)
+            && !is_paused.load(Ordering::SeqCst) {
+            std::thread::sleep((due - self.window_start.elapsed()).min(Duration::from_millis(100)));
+        }
+    }
+}
+
+/// Bounds how many SSH sessions are open to the same host at once, so parallel deploys and
+/// retries queue up instead of tripping the server's `MaxStartups` mid-handshake.
+/// Different hosts never wait on each other.
+#[derive(Debug, Default)]
+pub struct HostConnections {
+    max_per_host: AtomicUsize,
+    open: Mutex<HashMap<String, usize>>,
+    released: Condvar,
+}
+
+impl HostConnections {
+    pub fn from_config(config: &AppConfig) -> Self {
+        let connections = Self::default();
+        connections.apply(config.max_connections_per_host);
+        connections
+    }
+
+    pub fn apply(&self, max_per_host: usize) {
+        self.max_per_host.store(max_per_host.max(1), Ordering::SeqCst);
+        // A raised limit should let waiters in right away
+        self.released.notify_all();
+    }
+
+    /// Blocks until a slot for `host` is free; the slot is returned when the permit is dropped.
+    pub fn acquire(self: &Arc<Self>, host: &str, should_cancel: &AtomicBool) -> Result<HostPermit, String> {
+        let mut open = self.open.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if should_cancel.load(Ordering::SeqCst) {
+                return Err("Cancelled".to_string());
+            }
+            let count = open.entry(host.to_string()).or_insert(0);
+            if *count < self.max_per_host.load(Ordering::SeqCst) {
+                *count += 1;
+                return Ok(HostPermit { connections: self.clone(), host: host.to_string() });
+            }
+            // Wake up periodically so cancel stays responsive
+            open = self.released.wait_timeout(open, Duration::from_millis(200))
+                .unwrap_or_else(|e| e.into_inner()).0;
+        }
+    }
+}
+
+pub struct HostPermit {
+    connections: Arc<HostConnections>,
+    host: String,
+}
+
+impl Drop for HostPermit {
+    fn drop(&mut self) {
+        let mut open = self.connections.open.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = open.get_mut(&self.host) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                open.remove(&self.host);
+            }
+        }
+        self.connections.released.notify_all();
+    }
+}
+
+/// The app-wide connection slots, or a private set when there is no app state.
+pub fn host_connections<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> Arc<HostConnections> {
+    app_handle
+        .try_state::<AppState>()
+        .map(|state| state.host_connections.clone())
+        .unwrap_or_else(|| Arc::new(HostConnections::from_config(&AppConfig::default())))
+}
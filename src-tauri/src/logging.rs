@@ -0,0 +1,206 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use tauri::Manager;
+
+use crate::AppState;
+
+// Profile directories of any user, e.g. C:\Users\alice or /home/alice
+fn profile_dir_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?i)([a-z]:[\\/]users[\\/]|/home/|/users/)[^\\/\s:]+").unwrap())
+}
+
+/// Masks home-directory portions of paths: the current user's home becomes `~`,
+/// other profile directories keep their prefix but lose the user name.
+pub fn redact_paths(msg: &str) -> String {
+    let mut result = msg.to_string();
+    for var in ["USERPROFILE", "HOME"] {
+        if let Ok(home) = std::env::var(var) {
+            let home = home.trim_end_matches(['/', '\\']);
+            if home.len() > 1 {
+                result = result.replace(home, "~");
+            }
+        }
+    }
+    profile_dir_regex().replace_all(&result, "${1}***").to_string()
+}
+
+pub fn redaction_enabled<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> bool {
+    app_handle
+        .try_state::<AppState>()
+        .map(|state| state.read_config(|c| c.redact_paths))
+        .unwrap_or(false)
+}
+
+/// Message as it should appear in UI events; the log file always gets the original.
+pub fn ui_message<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, msg: String) -> String {
+    if redaction_enabled(app_handle) {
+        redact_paths(&msg)
+    } else {
+        msg
+    }
+}
+
+/// Severity of a log line, lowest first. `Success` is an info line worth highlighting, so it
+/// survives a `min_log_level` of info but not of warn. Read case-insensitively, since config.json
+/// is edited by hand.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase", try_from = "String")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    #[default]
+    Info,
+    Success,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Success => "success",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+impl TryFrom<String> for LogLevel {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "success" => Ok(LogLevel::Success),
+            "warn" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(format!("unknown log level \"{}\", expected trace, debug, info, success, warn or error", value)),
+        }
+    }
+}
+
+/// Whether lines of `level` pass `min_log_level`; below it they reach neither the UI nor app.log.
+pub fn level_enabled<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, level: LogLevel) -> bool {
+    app_handle
+        .try_state::<AppState>()
+        .map(|state| state.read_config(|c| level >= c.min_log_level))
+        .unwrap_or(true)
+}
+
+// Roughly a few scans' worth of UI log lines
+const MAX_RECENT_LOGS: usize = 2000;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: LogLevel,
+    pub category: String, // "scan", "copy", "deploy", "system"
+    pub run_id: Option<String>, // Scan the line was logged under, if any
+    pub msg: String,      // As shown in the UI, i.e. already redacted
+}
+
+/// The most recent UI log lines, so the console can be rebuilt after a reload and filtered.
+#[derive(Default)]
+pub struct RecentLogs {
+    entries: Mutex<VecDeque<LogEntry>>,
+    current_run: Mutex<Option<String>>,
+}
+
+pub fn record_log<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, level: LogLevel, category: &str, msg: &str) {
+    let Some(state) = app_handle.try_state::<RecentLogs>() else {
+        return;
+    };
+    let run_id = state.current_run.lock().unwrap().clone();
+    {
+        let mut entries = state.entries.lock().unwrap();
+        if entries.len() >= MAX_RECENT_LOGS {
+            entries.pop_front();
+        }
+        entries.push_back(LogEntry {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            level,
+            category: category.to_string(),
+            run_id: run_id.clone(),
+            msg: msg.to_string(),
+        });
+    }
+
+    if history_level_enabled(app_handle, level) {
+        crate::history::add_history_entry(app_handle, crate::history::HistoryEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: chrono::Local::now().to_rfc3339(),
+            action_type: "LOG".to_string(),
+            description: msg.to_string(),
+            folder_name: String::new(),
+            source_path: String::new(),
+            target_path: String::new(),
+            copied_files_count: 0,
+            total_size: 0,
+            files: vec![],
+            run_id: run_id.unwrap_or_default(),
+            command_results: vec![],
+        });
+    }
+}
+
+// Levels listed in history_from_log_levels outlive the in-memory console as LOG history entries
+fn history_level_enabled<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, level: LogLevel) -> bool {
+    app_handle
+        .try_state::<AppState>()
+        .map(|state| state.read_config(|c| c.history_from_log_levels.contains(&level)))
+        .unwrap_or(false)
+}
+
+/// Run id of the scan in progress, empty outside a scan.
+pub fn current_run_id<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> String {
+    app_handle.try_state::<RecentLogs>()
+        .and_then(|state| state.current_run.lock().unwrap().clone())
+        .unwrap_or_default()
+}
+
+/// Tags every log line with `run_id` until dropped.
+pub struct RunScope<R: tauri::Runtime> {
+    app_handle: tauri::AppHandle<R>,
+}
+
+impl<R: tauri::Runtime> RunScope<R> {
+    pub fn enter(app_handle: &tauri::AppHandle<R>, run_id: &str) -> Self {
+        if let Some(state) = app_handle.try_state::<RecentLogs>() {
+            *state.current_run.lock().unwrap() = Some(run_id.to_string());
+        }
+        Self { app_handle: app_handle.clone() }
+    }
+}
+
+impl<R: tauri::Runtime> Drop for RunScope<R> {
+    fn drop(&mut self) {
+        if let Some(state) = self.app_handle.try_state::<RecentLogs>() {
+            *state.current_run.lock().unwrap() = None;
+        }
+    }
+}
+
+/// Oldest first. `level` is a minimum level ("warn" also returns errors); every filter is optional.
+#[tauri::command]
+pub fn get_recent_logs(
+    state: tauri::State<RecentLogs>,
+    level: Option<LogLevel>,
+    category: Option<String>,
+    run_id: Option<String>
+) -> Vec<LogEntry> {
+    state.entries.lock().unwrap()
+        .iter()
+        .filter(|e| level.map_or(true, |min| e.level >= min))
+        .filter(|e| category.as_ref().map_or(true, |c| &e.category == c))
+        .filter(|e| run_id.is_none() || e.run_id == run_id)
+        .cloned()
+        .collect()
+}
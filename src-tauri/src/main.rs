@@ -5,10 +5,23 @@ mod config;
 mod scanner;
 mod history;
 mod deploy;
+mod timeline;
+mod logging;
+mod error;
+mod webhook;
+mod diagnostics;
+mod limits;
+mod selftest;
+mod benchmark;
+mod stats;
+mod credentials;
+mod walk;
 
 use config::{AppConfig, DeployServer};
+use logging::LogLevel;
 use scanner::ScanResult;
 use history::HistoryStore;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Mutex, Arc};
 use std::sync::atomic::{AtomicBool, Ordering};
 
@@ -16,24 +29,178 @@ use tauri::{State, Manager, Emitter};
 
 struct AppState {
     config: Mutex<AppConfig>,
+    config_error: Mutex<Option<String>>, // Set when config.json couldn't be loaded at startup
+    config_warnings: Mutex<Vec<String>>, // Loaded fine, but e.g. a password could not be decrypted
     is_scanning: Arc<AtomicBool>,
     should_cancel: Arc<AtomicBool>,
     is_paused: Arc<AtomicBool>,
+    is_self_testing: AtomicBool, // Its dummy build must not count in stats or as the last successful scan
+    // Deploys have their own controls, so a manual deploy can run (and be stopped) while a scan copies
+    is_deploying: Arc<AtomicBool>,
+    deploy_cancel: Arc<AtomicBool>,
+    deploy_paused: Arc<AtomicBool>,
+    limits: Arc<limits::RuntimeLimits>, // Live bandwidth/concurrency, seeded from config
+    host_connections: Arc<limits::HostConnections>, // Per-host SSH session slots shared by all deploys
+    stats: Arc<stats::RuntimeStats>, // Lifetime counters, persisted in stats.json
+    skipped_servers: Mutex<HashSet<String>>, // Server ids excluded from the deploy batch in progress
+    scheduled_scans: Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>, // One-shot scans not fired yet, by job id
+}
+
+// All config access goes through these so the lock is only held for the read/write itself,
+// never across an `.await` or a long-running scan/deploy
+impl AppState {
+    fn new(loaded: config::LoadedConfig, stats: stats::RuntimeStats) -> Self {
+        let config::LoadedConfig { config, error: config_error, warnings: config_warnings } = loaded;
+        Self {
+            limits: Arc::new(limits::RuntimeLimits::from_config(&config)),
+            host_connections: Arc::new(limits::HostConnections::from_config(&config)),
+            config: Mutex::new(config),
+            config_error: Mutex::new(config_error),
+            config_warnings: Mutex::new(config_warnings),
+            is_scanning: Arc::new(AtomicBool::new(false)),
+            should_cancel: Arc::new(AtomicBool::new(false)),
+            is_paused: Arc::new(AtomicBool::new(false)),
+            is_self_testing: AtomicBool::new(false),
+            is_deploying: Arc::new(AtomicBool::new(false)),
+            deploy_cancel: Arc::new(AtomicBool::new(false)),
+            deploy_paused: Arc::new(AtomicBool::new(false)),
+            stats: Arc::new(stats),
+            skipped_servers: Mutex::new(HashSet::new()),
+            scheduled_scans: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn read_config<T>(&self, f: impl FnOnce(&AppConfig) -> T) -> T {
+        // A panic elsewhere while holding the lock shouldn't take every later command down with it
+        let guard = self.config.lock().unwrap_or_else(|e| e.into_inner());
+        f(&guard)
+    }
+
+    fn config_snapshot(&self) -> AppConfig {
+        self.read_config(AppConfig::clone)
+    }
+
+    // What scans run with, i.e. with safe mode applied
+    fn effective_config(&self) -> AppConfig {
+        self.read_config(config::apply_safe_mode)
+    }
+
+    fn ensure_not_safe_mode(&self) -> Result<(), String> {
+        if self.read_config(|c| c.safe_mode) {
+            return Err("Disabled in safe mode".to_string());
+        }
+        Ok(())
+    }
+
+    fn replace_config(&self, config: AppConfig) {
+        *self.config.lock().unwrap_or_else(|e| e.into_inner()) = config;
+    }
 }
 
 #[tauri::command]
 fn get_config(state: State<AppState>) -> AppConfig {
-    state.config.lock().unwrap().clone()
+    state.config_snapshot()
+}
+
+// The config actually driving scans: already migrated and defaulted by load_config
+#[tauri::command]
+fn get_effective_config(state: State<AppState>) -> AppConfig {
+    config::redact_secrets(&state.effective_config())
+}
+
+#[tauri::command]
+fn test_pattern(pattern: String, datetime_format: String, sample_name: String) -> Result<scanner::PatternTest, String> {
+    scanner::test_pattern(&pattern, &datetime_format, &sample_name)
+}
+
+#[tauri::command]
+fn normalize_extensions(exts: Vec<String>) -> config::NormalizedExtensions {
+    config::normalize_extensions(&exts)
+}
+
+#[tauri::command]
+fn get_config_error(state: State<AppState>) -> Option<String> {
+    state.config_error.lock().unwrap().clone()
+}
+
+#[tauri::command]
+fn get_config_warnings(state: State<AppState>) -> Vec<String> {
+    state.config_warnings.lock().unwrap().clone()
+}
+
+#[tauri::command]
+fn validate_time_ranges(ranges: Vec<String>) -> Vec<String> {
+    config::validate_time_ranges(&ranges)
+}
+
+// Returns warnings that don't block saving, e.g. overlapping time ranges
+#[tauri::command]
+fn save_config_cmd(app_handle: tauri::AppHandle, state: State<AppState>, config: AppConfig) -> Result<Vec<String>, String> {
+    apply_config(&app_handle, &state, config)
+}
+
+fn apply_config(app_handle: &tauri::AppHandle, state: &AppState, config: AppConfig) -> Result<Vec<String>, String> {
+    config::validate_config(&config)?;
+    let warnings = config::validate_time_ranges(&config.time_ranges);
+    for warning in &warnings {
+        log::warn!("Time ranges: {}", warning);
+    }
+    state.replace_config(config.clone());
+    state.limits.apply(config.max_bytes_per_sec, config.max_concurrency);
+    state.host_connections.apply(config.max_connections_per_host);
+    config::save_config(app_handle, &config)?;
+    // A successful save replaces whatever was corrupt or missing
+    *state.config_error.lock().unwrap() = None;
+    state.config_warnings.lock().unwrap().clear();
+    Ok(warnings)
+}
+
+/// Writes the current config to `path` as plain JSON for another machine. Secrets stay in
+/// plaintext only with `include_secrets`; the keyring encryption of config.json doesn't travel.
+#[tauri::command]
+fn export_config(state: State<AppState>, path: String, include_secrets: bool) -> Result<(), String> {
+    let current = state.config_snapshot();
+    let exported = if include_secrets { current } else { config::strip_secrets(&current) };
+    let content = serde_json::to_string_pretty(&exported).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    log::info!("Exported config to {} (secrets {})", path, if include_secrets { "included" } else { "stripped" });
+    Ok(())
 }
 
+/// Replaces the config with the one exported to `path`, saving it like the settings page does.
+/// Returns the same non-blocking warnings as `save_config_cmd`, plus any password that could
+/// not be decrypted when `path` is a config.json copied as-is.
 #[tauri::command]
-fn save_config_cmd(app_handle: tauri::AppHandle, state: State<AppState>, config: AppConfig) -> Result<(), String> {
-    *state.config.lock().unwrap() = config.clone();
-    config::save_config(&app_handle, &config)
+fn import_config(app_handle: tauri::AppHandle, state: State<AppState>, path: String) -> Result<Vec<String>, String> {
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mut imported: AppConfig = serde_json::from_str(&content)
+        .map_err(|e| format!("{} is not a valid config: {}", path, e))?;
+    config::migrate_legacy(&mut imported);
+    let mut warnings = credentials::decrypt_loaded(&mut imported);
+    warnings.extend(apply_config(&app_handle, &state, imported)?);
+    log::info!("Imported config from {}", path);
+    Ok(warnings)
 }
 
 #[tauri::command]
 async fn scan_now(app_handle: tauri::AppHandle, state: State<'_, AppState>) -> Result<ScanResult, String> {
+    let (result, _webhook) = run_scan(&app_handle, &state).await?;
+    Ok(result)
+}
+
+// Runs `scan` on the config snapshot a scan works with. The lock is released before `scan` is
+// awaited, so settings stay readable (and savable) for the whole scan.
+async fn with_scan_config<F, Fut, T>(state: &AppState, scan: F) -> T
+where
+    F: FnOnce(AppConfig) -> Fut,
+    Fut: std::future::Future<Output = T>,
+{
+    let config = state.effective_config();
+    scan(config).await
+}
+
+// Shared by the scan_now command and --scan-once; the returned handle finishes when the webhook has been sent
+async fn run_scan(app_handle: &tauri::AppHandle, state: &AppState) -> Result<(ScanResult, tauri::async_runtime::JoinHandle<()>), String> {
     if state.is_scanning.load(Ordering::SeqCst) {
         return Err("Scan already in progress".to_string());
     }
@@ -42,11 +209,168 @@ async fn scan_now(app_handle: tauri::AppHandle, state: State<'_, AppState>) -> R
     state.should_cancel.store(false, Ordering::SeqCst);
     state.is_paused.store(false, Ordering::SeqCst);
     
-    let config = state.config.lock().unwrap().clone();
-    let result = scanner::scan_and_copy(&app_handle, &config, state.should_cancel.clone(), state.is_paused.clone()).await;
+    let started = std::time::Instant::now();
+    let (should_cancel, is_paused) = (state.should_cancel.clone(), state.is_paused.clone());
+    let (config, result) = with_scan_config(state, |config| async move {
+        let result = scanner::scan_and_copy(app_handle, &config, should_cancel, is_paused).await;
+        (config, result)
+    }).await;
     
     state.is_scanning.store(false, Ordering::SeqCst);
-    Ok(result)
+    deploy::clear_skipped_servers(app_handle);
+    scanner::report_scan_summary(app_handle, &result);
+    state.stats.update(|s| {
+        s.total_scans += 1;
+        s.total_folders_copied += result.copied_folders.len() as u64;
+        s.total_errors += result.errors.len() as u64;
+    });
+
+    let success = result.errors.is_empty();
+    let payload = serde_json::json!({
+        "event": "scan",
+        "success": success,
+        "scanned": result.scanned_paths,
+        "found": result.found_folders.len(),
+        "copied": result.copied_folders.len(),
+        "folders": result.copied_folders,
+        "errors": result.errors,
+        "phase_durations": result.phase_durations,
+        "duration_ms": started.elapsed().as_millis() as u64,
+    });
+    // Don't hold up the UI waiting on a slow endpoint
+    let webhook = tauri::async_runtime::spawn_blocking(move || webhook::notify(&config, success, payload));
+    Ok((result, webhook))
+}
+
+/// `--scan-once`: one scan without the UI, summary on stdout, then exit.
+/// Exit code 0 = no errors, 1 = the scan reported errors, 2 = the scan couldn't run.
+async fn run_scan_once(app_handle: tauri::AppHandle) {
+    let state = app_handle.state::<AppState>();
+    if let Some(err) = state.config_error.lock().unwrap().clone() {
+        eprintln!("Config could not be loaded: {}", err);
+        app_handle.exit(2);
+        return;
+    }
+    for warning in state.config_warnings.lock().unwrap().iter() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    let code = match run_scan(&app_handle, &state).await {
+        Ok((result, webhook)) => {
+            println!(
+                "Scanned {} path(s), found {} folder(s), copied {}: {}",
+                result.scanned_paths,
+                result.found_folders.len(),
+                result.copied_folders.len(),
+                if result.copied_folders.is_empty() { "-".to_string() } else { result.copied_folders.join(", ") }
+            );
+            for err in &result.errors {
+                eprintln!("Error: {}", err);
+            }
+            // Let the notification go out before the process ends
+            let _ = webhook.await;
+            if result.errors.is_empty() { 0 } else { 1 }
+        }
+        Err(e) => {
+            eprintln!("Scan failed: {}", e);
+            2
+        }
+    };
+    app_handle.exit(code);
+}
+
+/// Background scheduler: while `scheduler_enabled` is on, scans every `interval_minutes`.
+/// The config is re-read every minute, so a saved interval applies without a restart.
+async fn run_scheduler(app_handle: tauri::AppHandle) {
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_scan = std::time::Instant::now();
+    loop {
+        ticker.tick().await;
+        let state = app_handle.state::<AppState>();
+        let (enabled, interval_minutes) = state.read_config(|c| (c.scheduler_enabled, c.interval_minutes.max(1)));
+        if !enabled {
+            last_scan = std::time::Instant::now();
+            continue;
+        }
+        if last_scan.elapsed() < std::time::Duration::from_secs(interval_minutes * 60) {
+            continue;
+        }
+        // A manual scan or deploy is running; try again on the next tick
+        if state.is_scanning.load(Ordering::SeqCst) || state.is_deploying.load(Ordering::SeqCst) {
+            continue;
+        }
+        last_scan = std::time::Instant::now();
+        scanner::emit_system_log(&app_handle, format!("Automatic scan started (every {} min)", interval_minutes), LogLevel::Info);
+        if let Err(e) = run_scan(&app_handle, &state).await {
+            scanner::emit_system_log(&app_handle, format!("Automatic scan failed: {}", e), LogLevel::Error);
+        }
+    }
+}
+
+/// Runs one scan after `delay_secs`, e.g. when a build is expected to land, and returns the
+/// job id for `cancel_scheduled_scan`. A `scheduled-scan` event reports whether it ran.
+#[tauri::command]
+fn schedule_one_shot_scan(app_handle: tauri::AppHandle, state: State<AppState>, delay_secs: u64) -> String {
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let id = job_id.clone();
+    // Holding the lock across the spawn keeps a zero delay from firing before the job is registered
+    let mut scheduled = state.scheduled_scans.lock().unwrap_or_else(|e| e.into_inner());
+    let task = tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(delay_secs)).await;
+        let state = app_handle.state::<AppState>();
+        state.scheduled_scans.lock().unwrap_or_else(|e| e.into_inner()).remove(&id);
+
+        // Same rule as the recurring scheduler: never interrupt a scan or deploy in progress
+        let skipped = if state.is_scanning.load(Ordering::SeqCst) || state.is_deploying.load(Ordering::SeqCst) {
+            Some("a scan or deploy was already running".to_string())
+        } else {
+            scanner::emit_system_log(&app_handle, "Scheduled scan started".to_string(), LogLevel::Info);
+            run_scan(&app_handle, &state).await.err()
+        };
+        match &skipped {
+            Some(reason) => scanner::emit_system_log(&app_handle, format!("Scheduled scan skipped: {}", reason), LogLevel::Warn),
+            None => log::info!("Scheduled scan {} finished", id),
+        }
+        let _ = app_handle.emit("scheduled-scan", serde_json::json!({
+            "job_id": id,
+            "ran": skipped.is_none(),
+            "reason": skipped,
+        }));
+    });
+    scheduled.insert(job_id.clone(), task);
+    log::info!("Scan {} scheduled in {}s", job_id, delay_secs);
+    job_id
+}
+
+#[tauri::command]
+fn cancel_scheduled_scan(state: State<AppState>, job_id: String) -> Result<(), String> {
+    let task = state.scheduled_scans.lock().unwrap_or_else(|e| e.into_inner()).remove(&job_id);
+    match task {
+        Some(task) => {
+            task.abort();
+            log::info!("Scheduled scan {} cancelled", job_id);
+            Ok(())
+        }
+        None => Err(format!("No pending scheduled scan with id {}", job_id)),
+    }
+}
+
+#[tauri::command]
+fn get_runtime_stats(state: State<AppState>) -> stats::StatsSnapshot {
+    state.stats.snapshot()
+}
+
+#[tauri::command]
+fn reset_runtime_stats(state: State<AppState>) -> stats::StatsSnapshot {
+    state.stats.reset()
+}
+
+// Adjusts running transfers immediately; not persisted, the next config save wins
+#[tauri::command]
+fn set_runtime_limits(state: State<AppState>, bandwidth: u64, concurrency: usize) {
+    state.limits.apply(bandwidth, concurrency);
+    log::info!("Runtime limits set: {} bytes/s, concurrency {}", bandwidth, concurrency);
 }
 
 #[tauri::command]
@@ -56,6 +380,21 @@ fn cancel_scan(state: State<AppState>) {
     state.is_paused.store(false, Ordering::SeqCst);
 }
 
+// Like cancel_scan, but only returns once the scan has wound down (true) or the timeout hit (false).
+// A manual deploy running next to the scan is left alone.
+#[tauri::command]
+async fn cancel_scan_and_wait(state: State<'_, AppState>, timeout_secs: u64) -> Result<bool, String> {
+    if state.is_scanning.load(Ordering::SeqCst) {
+        state.should_cancel.store(true, Ordering::SeqCst);
+        state.is_paused.store(false, Ordering::SeqCst);
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+        while state.is_scanning.load(Ordering::SeqCst) && std::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+    }
+    Ok(!state.is_scanning.load(Ordering::SeqCst))
+}
+
 #[tauri::command]
 fn pause_scan(state: State<AppState>) {
     state.is_paused.store(true, Ordering::SeqCst);
@@ -66,35 +405,249 @@ fn resume_scan(state: State<AppState>) {
     state.is_paused.store(false, Ordering::SeqCst);
 }
 
+// Deploy controls act on manual deploys and on the deploy step of a running scan, never on its copying
+#[tauri::command]
+fn cancel_deploy(state: State<AppState>) {
+    state.deploy_cancel.store(true, Ordering::SeqCst);
+    state.deploy_paused.store(false, Ordering::SeqCst);
+}
+
+#[tauri::command]
+fn pause_deploy(state: State<AppState>) {
+    state.deploy_paused.store(true, Ordering::SeqCst);
+}
+
+#[tauri::command]
+fn resume_deploy(state: State<AppState>) {
+    state.deploy_paused.store(false, Ordering::SeqCst);
+}
+
+#[tauri::command]
+async fn test_ssh_connection(state: State<'_, AppState>, server: DeployServer) -> Result<String, String> {
+    config::validate_remote_path(&server)?;
+    let connect_timeout = state.read_config(|c| c.connect_timeout_secs);
+    tauri::async_runtime::spawn_blocking(move || deploy::check_connection(&server, connect_timeout))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
-async fn test_ssh_connection(server: DeployServer) -> Result<String, String> {
-    deploy::check_connection(&server)
+async fn test_remote_path(state: State<'_, AppState>, server: DeployServer) -> Result<String, String> {
+    config::validate_remote_path(&server)?;
+    let connect_timeout = state.read_config(|c| c.connect_timeout_secs);
+    tauri::async_runtime::spawn_blocking(move || deploy::check_remote_path(&server, connect_timeout))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn probe_server(state: State<'_, AppState>, server: DeployServer) -> Result<deploy::ServerProbe, String> {
+    config::validate_remote_path(&server)?;
+    let connect_timeout = state.read_config(|c| c.connect_timeout_secs);
+    tauri::async_runtime::spawn_blocking(move || deploy::probe_server(&server, connect_timeout))
+        .await
+        .map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
 async fn manual_deploy(app_handle: tauri::AppHandle, state: State<'_, AppState>, server: DeployServer, postCommands: Vec<String>, localPath: String, remotePath: String) -> Result<(), String> {
-    if state.is_scanning.load(Ordering::SeqCst) {
-        return Err("Operation already in progress".to_string());
+    state.ensure_not_safe_mode()?;
+    config::validate_remote_path(&server)?;
+    config::validate_posix_path(&server, &remotePath)?;
+    if state.is_deploying.swap(true, Ordering::SeqCst) {
+        return Err("A deployment is already in progress".to_string());
     }
-    
-    state.is_scanning.store(true, Ordering::SeqCst);
-    state.should_cancel.store(false, Ordering::SeqCst);
-    state.is_paused.store(false, Ordering::SeqCst);
+    state.deploy_cancel.store(false, Ordering::SeqCst);
+    state.deploy_paused.store(false, Ordering::SeqCst);
 
-    let should_cancel = state.should_cancel.clone();
-    let is_paused = state.is_paused.clone();
-    let is_scanning = state.is_scanning.clone();
+    let should_cancel = state.deploy_cancel.clone();
+    let is_paused = state.deploy_paused.clone();
+    let is_deploying = state.is_deploying.clone();
+    let config = state.config_snapshot();
+    let redact = config.redact_paths;
+    let handle = app_handle.clone();
 
     // This runs in async context, but deploy_manual uses blocking SSH.
     // We should spawn blocking.
     let result = tauri::async_runtime::spawn_blocking(move || {
-        deploy::deploy_manual(&app_handle, &server, &postCommands, &localPath, &remotePath, should_cancel, is_paused)
-    }).await.map_err(|e| e.to_string())?;
+        let started = std::time::Instant::now();
+        let result = deploy::deploy_manual(&app_handle, &config, &server, &postCommands, &localPath, &remotePath, should_cancel, is_paused);
+        let local = std::path::Path::new(&localPath);
+        let folder_name = local.file_name().unwrap_or_default().to_string_lossy().to_string();
+        deploy::record_deploy_history(&app_handle, &server, &folder_name, local, &remotePath, &result);
+        let result = result.map(|_| ());
+        let payload = serde_json::json!({
+            "event": "deploy",
+            "success": result.is_ok(),
+            "folder": localPath,
+            "servers": [server.name],
+            "errors": result.as_ref().err().map(|e| logging::ui_message(&app_handle, e.clone())).into_iter().collect::<Vec<_>>(),
+            "duration_ms": started.elapsed().as_millis() as u64,
+        });
+        webhook::notify(&config, result.is_ok(), payload);
+        result
+    }).await;
     
-    is_scanning.store(false, Ordering::SeqCst);
+    is_deploying.store(false, Ordering::SeqCst);
+    deploy::clear_skipped_servers(&handle);
+    let result = result.map_err(|e| e.to_string())?;
+    if redact {
+        return result.map_err(|e| logging::redact_paths(&e));
+    }
     result
 }
 
+#[tauri::command]
+async fn deploy_resume(app_handle: tauri::AppHandle, state: State<'_, AppState>, server: DeployServer, local_path: String) -> Result<(), String> {
+    state.ensure_not_safe_mode()?;
+    if state.is_deploying.swap(true, Ordering::SeqCst) {
+        return Err("A deployment is already in progress".to_string());
+    }
+    state.deploy_cancel.store(false, Ordering::SeqCst);
+    state.deploy_paused.store(false, Ordering::SeqCst);
+
+    let should_cancel = state.deploy_cancel.clone();
+    let is_paused = state.deploy_paused.clone();
+    let config = state.config_snapshot();
+    let handle = app_handle.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        deploy::deploy_resume(&app_handle, &config, &server, &local_path, should_cancel, is_paused)
+    }).await;
+
+    state.is_deploying.store(false, Ordering::SeqCst);
+    deploy::clear_skipped_servers(&handle);
+    result.map_err(|e| e.to_string())?
+}
+
+// Skips one server for the deploy batch in progress without touching its `enabled` flag.
+// A server whose deploy is already running finishes (or can be cancelled as a whole).
+#[tauri::command]
+fn disable_server_for_run(state: State<AppState>, server_id: String) -> Result<(), String> {
+    if !state.is_scanning.load(Ordering::SeqCst) && !state.is_deploying.load(Ordering::SeqCst) {
+        return Err("No scan or deploy is running".to_string());
+    }
+    state.skipped_servers.lock().unwrap_or_else(|e| e.into_inner()).insert(server_id);
+    Ok(())
+}
+
+#[tauri::command]
+async fn retry_failed_servers(app_handle: tauri::AppHandle, state: State<'_, AppState>, local_path: String, folder_name: String) -> Result<deploy::RetryOutcome, String> {
+    state.ensure_not_safe_mode()?;
+    if state.is_deploying.swap(true, Ordering::SeqCst) {
+        return Err("A deployment is already in progress".to_string());
+    }
+    state.deploy_cancel.store(false, Ordering::SeqCst);
+    state.deploy_paused.store(false, Ordering::SeqCst);
+
+    let should_cancel = state.deploy_cancel.clone();
+    let is_paused = state.deploy_paused.clone();
+    let config = state.config_snapshot();
+    let handle = app_handle.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        deploy::retry_failed_servers(&app_handle, &config, &local_path, &folder_name, should_cancel, is_paused)
+    }).await;
+
+    state.is_deploying.store(false, Ordering::SeqCst);
+    deploy::clear_skipped_servers(&handle);
+    result.map_err(|e| e.to_string())?
+}
+
+// Cancels the running deploy (manual, resumed or started by a scan) and waits for it to exit,
+// leaving scans alone. Returns whether no deploy is running anymore.
+async fn stop_deploy_and_wait(state: &AppState, timeout: std::time::Duration) -> bool {
+    if state.is_deploying.load(Ordering::SeqCst) {
+        state.deploy_cancel.store(true, Ordering::SeqCst);
+        state.deploy_paused.store(false, Ordering::SeqCst);
+        let deadline = std::time::Instant::now() + timeout;
+        while state.is_deploying.load(Ordering::SeqCst) && std::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+    }
+    !state.is_deploying.load(Ordering::SeqCst)
+}
+
+// Cancels everything (and unpauses so loops can see the cancel), then waits for the
+// running operation to exit. Returns the operations still running when `timeout` hit.
+async fn stop_all_and_wait(state: &AppState, timeout: std::time::Duration) -> Vec<String> {
+    let running = |state: &AppState| state.is_scanning.load(Ordering::SeqCst) || state.is_deploying.load(Ordering::SeqCst);
+    if running(state) {
+        state.should_cancel.store(true, Ordering::SeqCst);
+        state.is_paused.store(false, Ordering::SeqCst);
+        state.deploy_cancel.store(true, Ordering::SeqCst);
+        state.deploy_paused.store(false, Ordering::SeqCst);
+        let deadline = std::time::Instant::now() + timeout;
+        while running(state) && std::time::Instant::now() < deadline {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+    }
+
+    let mut still_running = vec![];
+    if state.is_scanning.load(Ordering::SeqCst) {
+        still_running.push("scan".to_string());
+    }
+    if state.is_deploying.load(Ordering::SeqCst) {
+        still_running.push("deploy".to_string());
+    }
+    still_running
+}
+
+#[tauri::command]
+async fn emergency_stop(state: State<'_, AppState>, timeout_secs: Option<u64>) -> Result<Vec<String>, String> {
+    let timeout = std::time::Duration::from_secs(timeout_secs.unwrap_or(30));
+    let still_running = stop_all_and_wait(&state, timeout).await;
+    if still_running.is_empty() {
+        log::info!("Emergency stop: all operations stopped");
+    } else {
+        log::warn!("Emergency stop: still running after {:?}: {:?}", timeout, still_running);
+    }
+    Ok(still_running)
+}
+
+#[tauri::command]
+async fn list_remote_dir(app_handle: tauri::AppHandle, server: DeployServer, path: String) -> Result<Vec<deploy::RemoteEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || deploy::list_remote_dir(&app_handle, &server, &path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn get_directory_tree(state: State<'_, AppState>, remote_path: String, max_entries: usize) -> Result<scanner::DirectoryTree, String> {
+    let config = state.config_snapshot();
+    scanner::directory_tree(&config, &remote_path, max_entries).await
+}
+
+#[tauri::command]
+async fn rerun_post_commands(app_handle: tauri::AppHandle, state: State<'_, AppState>, server: DeployServer, folder_name: String, local_path: String) -> Result<Vec<deploy::CommandResult>, String> {
+    state.ensure_not_safe_mode()?;
+    let config = state.config_snapshot();
+    tauri::async_runtime::spawn_blocking(move || {
+        deploy::rerun_post_commands(&app_handle, &config, &server, &folder_name, &local_path)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn rollback_deploy(app_handle: tauri::AppHandle, state: State<'_, AppState>, server: DeployServer, remote_path: String) -> Result<(), String> {
+    state.ensure_not_safe_mode()?;
+    // Stop whatever is still uploading before we restore over it; a scan's local copy carries on
+    if !stop_deploy_and_wait(&state, std::time::Duration::from_secs(30)).await {
+        return Err("Running deployment did not stop in time; rollback aborted".to_string());
+    }
+
+    let rollback_commands = state.read_config(|c| c.rollback_commands.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        deploy::rollback_deploy(&app_handle, &server, &remote_path, &rollback_commands)
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+async fn cleanup_staging(app_handle: tauri::AppHandle, state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    state.ensure_not_safe_mode()?;
+    let config = state.config_snapshot();
+    tauri::async_runtime::spawn_blocking(move || scanner::cleanup_staging(&app_handle, &config))
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn get_app_paths(app_handle: tauri::AppHandle) -> (String, String) {
     let config = config::get_config_path(&app_handle).to_string_lossy().to_string();
@@ -103,36 +656,118 @@ fn get_app_paths(app_handle: tauri::AppHandle) -> (String, String) {
 }
 
 fn main() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+    let scan_once = std::env::args().any(|arg| arg == "--scan-once");
+
+    let mut builder = tauri::Builder::default();
+    // A scripted run must not be swallowed by an already open window
+    if !scan_once {
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
             let _ = app.emit("single-instance", ());
-        }))
+        }));
+    }
+    builder
         .plugin(tauri_plugin_log::Builder::default().build())
         .plugin(tauri_plugin_clipboard_manager::init())
-        .setup(|app| {
-            let config = config::load_config(app.handle());
-            app.manage(AppState {
-                config: Mutex::new(config),
-                is_scanning: Arc::new(AtomicBool::new(false)),
-                should_cancel: Arc::new(AtomicBool::new(false)),
-                is_paused: Arc::new(AtomicBool::new(false)),
+        .manage(timeline::TransferTimelines::default())
+        .manage(logging::RecentLogs::default())
+        .setup(move |app| {
+            let loaded = config::load_config(app.handle());
+            app.manage(AppState::new(loaded, stats::RuntimeStats::load(app.handle())));
+
+            // Sweep staging folders left behind by a crash or power loss
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                let config = handle.state::<AppState>().config_snapshot();
+                // Safe mode promises no deletions, the sweep included
+                if !config.safe_mode {
+                    scanner::cleanup_staging(&handle, &config);
+                }
             });
+
+            if scan_once {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.hide();
+                }
+                tauri::async_runtime::spawn(run_scan_once(app.handle().clone()));
+            } else {
+                tauri::async_runtime::spawn(run_scheduler(app.handle().clone()));
+            }
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             get_config, 
-            save_config_cmd, 
+            get_config_error,
+            get_config_warnings,
+            normalize_extensions,
+            validate_time_ranges,
+            test_pattern,
+            get_effective_config,
+            save_config_cmd,
+            export_config,
+            import_config, 
             scan_now,
             cancel_scan,
+            cancel_scan_and_wait,
+            set_runtime_limits,
             pause_scan,
             resume_scan,
             history::get_history,
             history::clear_history,
             history::add_system_event,
+            history::get_last_successful_scan,
+            timeline::get_transfer_timeline,
+            logging::get_recent_logs,
             test_ssh_connection,
+            test_remote_path,
+            probe_server,
+            list_remote_dir,
             manual_deploy,
+            cancel_deploy,
+            pause_deploy,
+            resume_deploy,
+            deploy_resume,
+            retry_failed_servers,
+            disable_server_for_run,
+            rollback_deploy,
+            rerun_post_commands,
+            get_directory_tree,
+            emergency_stop,
+            cleanup_staging,
+            diagnostics::export_diagnostics,
+            selftest::self_test,
+            benchmark::benchmark_source,
+            schedule_one_shot_scan,
+            cancel_scheduled_scan,
+            get_runtime_stats,
+            reset_runtime_stats,
             get_app_paths
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_stays_readable_while_a_scan_runs() {
+        let stats = stats::RuntimeStats::load_from(std::env::temp_dir().join("file-sync-test-stats.json"));
+        let state = AppState::new(config::LoadedConfig { config: AppConfig::default(), error: None, warnings: vec![] }, stats);
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        // The scan future stands in for scan_and_copy: while it is suspended, the UI reads and saves settings
+        let state_ref = &state;
+        let scanned_with = runtime.block_on(with_scan_config(state_ref, |config| async move {
+            tokio::task::yield_now().await;
+            assert!(state_ref.config.try_lock().is_ok(), "the config lock must not be held across the scan");
+            let mut edited = state_ref.config_snapshot();
+            edited.local_path = "edited-while-scanning".to_string();
+            state_ref.replace_config(edited);
+            config
+        }));
+
+        assert_eq!(state.read_config(|c| c.local_path.clone()), "edited-while-scanning");
+        assert_ne!(scanned_with.local_path, "edited-while-scanning");
+    }
+}
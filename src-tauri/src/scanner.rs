@@ -1,14 +1,20 @@
-use crate::config::{AppConfig, MatchRule};
-use crate::history::{add_history_entry, HistoryEntry};
-use crate::deploy::deploy_to_remote;
+use crate::config::{AppConfig, MatchRule, ScanTask, SyncMode, DEFAULT_FOLDER_PATTERN};
+use crate::history::{add_history_entry, record_successful_scan, HistoryEntry};
+use crate::timeline::record_sample;
+use crate::logging::{level_enabled, record_log, redact_paths, ui_message, LogLevel, RunScope};
+use crate::deploy::{deploy_to_remote, FOLDER_DESCRIPTOR_NAME};
+use crate::error::SyncError;
+use crate::limits::{live_limits, ConcurrencyGate, Throttle};
+use crate::walk::{SymlinkGuard, WalkEntry};
 use chrono::{Local, NaiveDateTime, Duration, NaiveTime};
 use regex::Regex;
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 use tokio::fs;
-use tauri::{Emitter, Manager};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Instant;
+use tauri::Emitter;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Instant, SystemTime};
 use std::io::{Read, Write};
 use std::fs::OpenOptions;
 
@@ -17,13 +23,213 @@ pub struct ScanResult {
     pub scanned_paths: usize,
     pub found_folders: Vec<String>,
     pub copied_folders: Vec<String>,
+    pub failed_folders: Vec<String>, // Copy failed, or copied but the deploy failed
     pub errors: Vec<String>,
+    pub phase_durations: PhaseDurations,
+    pub run_id: String, // Ties this scan's history entries together
+    pub bytes_copied: u64, // Across every folder and local destination
+    pub duration_ms: u64,
+}
+
+// Wall-clock time per scan phase, so a slow scan can be pinned on listing, matching, copying or deploying
+#[derive(Debug, serde::Serialize, Clone, Default)]
+pub struct PhaseDurations {
+    pub enumeration_ms: u64,
+    pub filtering_ms: u64,
+    pub copy_ms: u64,
+    pub deploy_ms: u64,
+}
+
+/// Collects one scan's `ScanResult` behind a mutex, so folder copies running on other
+/// threads can report into it directly. Clones share the same result.
+#[derive(Clone)]
+struct ScanReport {
+    result: Arc<Mutex<ScanResult>>,
+    overall: Arc<OverallProgress>,
+    started: Instant,
+}
+
+impl ScanReport {
+    fn new() -> Self {
+        Self {
+            result: Arc::new(Mutex::new(ScanResult {
+                scanned_paths: 0,
+                found_folders: vec![],
+                copied_folders: vec![],
+                failed_folders: vec![],
+                errors: vec![],
+                phase_durations: PhaseDurations::default(),
+                run_id: uuid::Uuid::new_v4().to_string(),
+                bytes_copied: 0,
+                duration_ms: 0,
+            })),
+            overall: Arc::new(OverallProgress::default()),
+            started: Instant::now(),
+        }
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut ScanResult) -> T) -> T {
+        f(&mut self.result.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    fn run_id(&self) -> String {
+        self.update(|r| r.run_id.clone())
+    }
+
+    fn error(&self, msg: String) {
+        self.update(|r| r.errors.push(msg));
+    }
+
+    fn found(&self, folder: String) {
+        self.update(|r| r.found_folders.push(folder));
+    }
+
+    fn copied(&self, folder: String) {
+        self.update(|r| r.copied_folders.push(folder));
+    }
+
+    fn failed(&self, folder: String) {
+        self.update(|r| if !r.failed_folders.contains(&folder) { r.failed_folders.push(folder) });
+    }
+
+    fn phases(&self, f: impl FnOnce(&mut PhaseDurations)) {
+        self.update(|r| f(&mut r.phase_durations));
+    }
+
+    fn finish(&self) -> ScanResult {
+        let bytes_copied = self.overall.copied.load(Ordering::SeqCst);
+        let duration_ms = self.started.elapsed().as_millis() as u64;
+        self.update(|r| {
+            r.bytes_copied = bytes_copied;
+            r.duration_ms = duration_ms;
+            r.clone()
+        })
+    }
+
+    // A copy to one of the additional_local_paths: same run and overall progress, but the folder
+    // is not counted as found or copied a second time
+    fn mirror(&self) -> Self {
+        Self {
+            result: Arc::new(Mutex::new(ScanResult {
+                scanned_paths: 0,
+                found_folders: vec![],
+                copied_folders: vec![],
+                failed_folders: vec![],
+                errors: vec![],
+                phase_durations: PhaseDurations::default(),
+                run_id: self.run_id(),
+                bytes_copied: 0,
+                duration_ms: 0,
+            })),
+            overall: self.overall.clone(),
+            started: self.started,
+        }
+    }
+
+    fn absorb_mirror(&self, mirror: &ScanReport) {
+        let mirrored = mirror.finish();
+        self.update(|r| {
+            r.errors.extend(mirrored.errors);
+            for folder in mirrored.failed_folders {
+                if !r.failed_folders.contains(&folder) {
+                    r.failed_folders.push(folder);
+                }
+            }
+            r.phase_durations.copy_ms += mirrored.phase_durations.copy_ms;
+        });
+    }
+}
+
+/// Bytes across every folder of one scan, next to the per-folder `copy-progress`. The total is
+/// estimated before the first copy starts (files not at the destination yet, before the
+/// extension/name filters) and corrected to each folder's filtered size once it is listed.
+/// Keyed by destination, so a folder copied to several local paths counts once per copy.
+#[derive(Default)]
+struct OverallProgress {
+    copied: AtomicU64,
+    total: AtomicU64,
+    estimates: Mutex<HashMap<PathBuf, u64>>,
+}
+
+#[derive(Debug, serde::Serialize, Clone)]
+struct OverallProgressEvent {
+    copied_bytes: u64,
+    total_bytes: u64,
+    percentage: f64,
+}
+
+impl OverallProgress {
+    fn estimate(&self, target: PathBuf, bytes: u64) {
+        self.estimates.lock().unwrap_or_else(|e| e.into_inner()).insert(target, bytes);
+        self.total.fetch_add(bytes, Ordering::SeqCst);
+    }
+
+    // Swaps a folder's estimate for the size it actually copies
+    fn settle(&self, target: &Path, actual: u64) {
+        let estimate = self.estimates.lock().unwrap_or_else(|e| e.into_inner()).remove(target).unwrap_or(0);
+        let _ = self.total.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |total| Some((total + actual).saturating_sub(estimate)));
+    }
+
+    fn add(&self, bytes: u64) {
+        self.copied.fetch_add(bytes, Ordering::SeqCst);
+    }
+
+    fn emit<R: tauri::Runtime>(&self, app_handle: &tauri::AppHandle<R>) {
+        let copied = self.copied.load(Ordering::SeqCst);
+        let total = self.total.load(Ordering::SeqCst).max(copied);
+        let _ = app_handle.emit("overall-progress", OverallProgressEvent {
+            copied_bytes: copied,
+            total_bytes: total,
+            percentage: if total > 0 { copied as f64 / total as f64 * 100.0 } else { 0.0 },
+        });
+    }
+}
+
+// Bytes under `source` that are not at `target` yet; the unfiltered upper bound of what a copy moves
+fn pending_bytes(source: &Path, target: &Path) -> u64 {
+    let mut bytes = 0;
+    let mut dirs_to_visit = vec![source.to_path_buf()];
+    while let Some(dir) = dirs_to_visit.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(metadata) = entry.metadata() else { continue };
+            if metadata.is_dir() {
+                dirs_to_visit.push(path);
+            } else if !target.join(path.strip_prefix(source).unwrap_or(&path)).exists() {
+                bytes += metadata.len();
+            }
+        }
+    }
+    bytes
+}
+
+// A folder picked by a task, copied once every task has been matched
+struct CopyJob {
+    source_path: PathBuf,
+    folder_name: String,
+    target_parent: PathBuf,
+}
+
+// Copies of `job` under each additional_local_paths entry, at the same place relative to
+// local_path. Jobs of tasks with their own local_path are not mirrored.
+fn mirror_jobs(job: &CopyJob, config: &AppConfig) -> Vec<CopyJob> {
+    let Ok(relative) = job.target_parent.strip_prefix(&config.local_path) else { return vec![] };
+    config.additional_local_paths.iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty() && Path::new(p) != Path::new(&config.local_path))
+        .map(|root| CopyJob {
+            source_path: job.source_path.clone(),
+            folder_name: job.folder_name.clone(),
+            target_parent: Path::new(root).join(relative),
+        })
+        .collect()
 }
 
 #[derive(Debug, serde::Serialize, Clone)]
 struct LogEvent {
     msg: String,
-    level: String,
+    level: LogLevel,
 }
 
 #[derive(Debug, serde::Serialize, Clone)]
@@ -39,31 +245,386 @@ struct ProgressEvent {
     remote_path: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Candidate {
     path: PathBuf,
     name: String,
     version: String,
     datetime: NaiveDateTime,
+    mtime: Option<SystemTime>,
+}
+
+struct FolderPatterns {
+    regexes: Vec<Regex>,
+    errors: Vec<String>, // One message per pattern that failed to compile
+}
+
+fn compile_folder_patterns(patterns: &[String]) -> FolderPatterns {
+    let mut compiled = FolderPatterns { regexes: vec![], errors: vec![] };
+    let defaults = [DEFAULT_FOLDER_PATTERN.to_string()];
+    let patterns = if patterns.is_empty() { &defaults[..] } else { patterns };
+    for pattern in patterns {
+        match Regex::new(pattern) {
+            Ok(re) => compiled.regexes.push(re),
+            Err(e) => compiled.errors.push(format!("Invalid folder pattern {}: {}", pattern, e)),
+        }
+    }
+    compiled
+}
+
+struct ParsedFolderName {
+    pattern_index: usize,
+    datetime: NaiveDateTime, // MIN when the captured text isn't a valid date
+    version: String,
+}
+
+const DEFAULT_DATETIME_FORMAT: &str = "%Y_%m_%d_%H_%M";
+
+// Date-only formats (no hour/minute) are read as midnight
+fn parse_folder_datetime(text: &str, format: &str) -> Result<NaiveDateTime, chrono::ParseError> {
+    NaiveDateTime::parse_from_str(text, format)
+        .or_else(|e| chrono::NaiveDate::parse_from_str(text, format).map(|d| d.and_time(NaiveTime::MIN)).map_err(|_| e))
+}
+
+// Named groups `datetime`/`version` are used when present, otherwise groups 1 and 2, like the built-in pattern
+fn folder_name_groups<'a>(re: &Regex, name: &'a str) -> Option<(Option<&'a str>, Option<&'a str>)> {
+    let caps = re.captures(name)?;
+    let datetime = caps.name("datetime").or_else(|| caps.get(1)).map(|m| m.as_str());
+    let version = caps.name("version").or_else(|| caps.get(2)).map(|m| m.as_str());
+    Some((datetime, version))
+}
+
+// The configured datetime format, or the built-in one when it's empty
+fn datetime_format_or_default(format: &str) -> &str {
+    if format.trim().is_empty() { DEFAULT_DATETIME_FORMAT } else { format.trim() }
+}
+
+// First matching pattern wins. An empty or missing version becomes `default_version`.
+fn parse_folder_name(patterns: &[Regex], datetime_format: &str, name: &str, default_version: &str) -> Option<ParsedFolderName> {
+    let datetime_format = datetime_format_or_default(datetime_format);
+    patterns.iter().enumerate().find_map(|(pattern_index, re)| {
+        let (datetime, version) = folder_name_groups(re, name)?;
+        let datetime = datetime
+            .and_then(|text| parse_folder_datetime(text, datetime_format).ok())
+            .unwrap_or(NaiveDateTime::MIN);
+        let version = version
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| default_version.to_string());
+        Some(ParsedFolderName { pattern_index, datetime, version })
+    })
+}
+
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct PatternTest {
+    pub matched: bool,
+    pub datetime_text: Option<String>, // What the datetime group captured
+    pub datetime: Option<String>,      // Parsed, "YYYY-MM-DD HH:MM"
+    pub datetime_error: Option<String>,
+    pub version: Option<String>,
+}
+
+/// Runs one folder pattern against a sample name the way a scan would. An empty
+/// `datetime_format` means the built-in `%Y_%m_%d_%H_%M`.
+pub fn test_pattern(pattern: &str, datetime_format: &str, sample_name: &str) -> Result<PatternTest, String> {
+    let re = Regex::new(pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+    let format = datetime_format_or_default(datetime_format);
+
+    let Some((datetime_text, version)) = folder_name_groups(&re, sample_name) else {
+        return Ok(PatternTest { matched: false, datetime_text: None, datetime: None, datetime_error: None, version: None });
+    };
+    let (datetime, datetime_error) = match datetime_text.map(|text| parse_folder_datetime(text, format)) {
+        Some(Ok(dt)) => (Some(dt.format("%Y-%m-%d %H:%M").to_string()), None),
+        Some(Err(e)) => (None, Some(format!("\"{}\" doesn't fit {}: {}", datetime_text.unwrap_or_default(), format, e))),
+        None => (None, Some("Pattern has no datetime group".to_string())),
+    };
+    Ok(PatternTest {
+        matched: true,
+        datetime_text: datetime_text.map(str::to_string),
+        datetime,
+        datetime_error,
+        version: version.map(str::to_string),
+    })
+}
+
+// Newest first; equal datetimes are broken by mtime (unless "name") and then by name
+// so repeated scans always pick the same folder.
+fn compare_candidates(a: &Candidate, b: &Candidate, tie_breaker: &str) -> std::cmp::Ordering {
+    let mut ord = b.datetime.cmp(&a.datetime);
+    if ord == std::cmp::Ordering::Equal && tie_breaker != "name" {
+        ord = b.mtime.cmp(&a.mtime);
+    }
+    ord.then_with(|| b.name.cmp(&a.name))
+}
+
+// The candidates a VersionMatch task picks, newest first. Versions compare whole, so UMS never
+// picks UMS2. With no version configured it matches nothing (legacy) or every versioned
+// candidate, minus the versions another task on the same path asks for by name: the more
+// specific task copies those.
+fn select_version_matches<'a>(config: &AppConfig, task: &ScanTask, target_version: &str, candidates: &'a [Candidate]) -> Vec<&'a Candidate> {
+    let match_any = target_version.is_empty() && config.empty_versions_behavior == "newest_any";
+    let claimed_versions: Vec<&String> = config.tasks.iter()
+        .filter(|t| t.enabled && t.id != task.id && t.remote_path == task.remote_path)
+        .filter_map(|t| match &t.rule {
+            MatchRule::VersionMatch(v) if !v.is_empty() => Some(v),
+            _ => None,
+        })
+        .collect();
+
+    let mut version_matches: Vec<&Candidate> = candidates.iter()
+        .filter(|c| if match_any {
+            !c.version.is_empty() && !claimed_versions.contains(&&c.version)
+        } else {
+            c.version == target_version
+        })
+        .collect();
+    version_matches.sort_by(|a, b| compare_candidates(a, b, &config.tie_breaker));
+    version_matches
+}
+
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct TreeEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub version: Option<String>,  // None when the name fits no folder pattern
+    pub datetime: Option<String>, // "YYYY-MM-DD HH:MM" from the folder name
+    pub modified: Option<String>, // Local mtime, RFC 3339
+    pub matched: bool, // An enabled task on this path would pick this version
+}
+
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct DirectoryTree {
+    pub entries: Vec<TreeEntry>, // Same order the scanner uses: newest build first
+    pub total: usize,
+    pub truncated: bool,
+}
+
+/// Lists `remote_path` the way a scan sees it, without copying anything.
+/// `max_entries` of 0 returns everything.
+pub async fn directory_tree(config: &AppConfig, remote_path: &str, max_entries: usize) -> Result<DirectoryTree, String> {
+    let folder_patterns = compile_folder_patterns(&config.folder_patterns);
+    let mut entries = fs::read_dir(remote_path).await
+        .map_err(|e| format!("Failed to read {}: {}", remote_path, e))?;
+
+    // Versions the enabled tasks for this path are looking for; an empty target means "any" only with newest_any
+    let same_path = |p: &str| p.trim_end_matches(['/', '\\']) == remote_path.trim_end_matches(['/', '\\']);
+    let mut target_versions: Vec<&str> = vec![];
+    let mut match_any = false;
+    for task in config.tasks.iter().filter(|t| t.enabled && same_path(&t.remote_path)) {
+        if let MatchRule::VersionMatch(version) = &task.rule {
+            if version.is_empty() {
+                match_any |= config.empty_versions_behavior == "newest_any";
+            } else {
+                target_versions.push(version);
+            }
+        }
+    }
+
+    let mut candidates: Vec<(Candidate, bool, bool)> = vec![];
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let metadata = entry.metadata().await.ok();
+        let parsed = parse_folder_name(&folder_patterns.regexes, &config.datetime_format, &name, &config.default_version);
+        let fits_pattern = parsed.is_some();
+        let (datetime, version) = parsed.map(|p| (p.datetime, p.version)).unwrap_or((NaiveDateTime::MIN, String::new()));
+        candidates.push((
+            Candidate {
+                path: entry.path(),
+                name,
+                version,
+                datetime,
+                mtime: metadata.as_ref().and_then(|m| m.modified().ok()),
+            },
+            metadata.map(|m| m.is_dir()).unwrap_or(false),
+            fits_pattern,
+        ));
+    }
+    candidates.sort_by(|a, b| compare_candidates(&a.0, &b.0, &config.tie_breaker));
+
+    let total = candidates.len();
+    let limit = if max_entries == 0 { total } else { max_entries.min(total) };
+    let entries = candidates.into_iter().take(limit).map(|(cand, is_dir, parsed)| {
+        let matched = parsed && is_dir
+            && ((match_any && !cand.version.is_empty()) || target_versions.contains(&cand.version.as_str()));
+        TreeEntry {
+            is_dir,
+            version: parsed.then(|| cand.version.clone()),
+            datetime: (parsed && cand.datetime != NaiveDateTime::MIN).then(|| cand.datetime.format("%Y-%m-%d %H:%M").to_string()),
+            modified: cand.mtime.map(|t| chrono::DateTime::<Local>::from(t).to_rfc3339()),
+            matched,
+            name: cand.name,
+        }
+    }).collect();
+
+    Ok(DirectoryTree { entries, total, truncated: limit < total })
+}
+
+const SCAN_LOCK_NAME: &str = ".file-sync.lock";
+
+// Held for the whole scan. The OS releases the lock when the process dies, so a lock file
+// left behind by a crash is simply re-acquired; its contents are informational only.
+struct ScanLock {
+    _file: std::fs::File,
+}
+
+fn acquire_scan_locks(config: &AppConfig) -> Result<Vec<ScanLock>, String> {
+    use fs2::FileExt;
+
+    let mut destinations: Vec<PathBuf> = vec![PathBuf::from(&config.local_path)];
+    destinations.extend(config.additional_local_paths.iter().map(PathBuf::from));
+    for task in config.tasks.iter().filter(|t| t.enabled) {
+        if let Some(local) = &task.local_path {
+            destinations.push(PathBuf::from(local));
+        }
+    }
+    let mut seen = HashSet::new();
+    destinations.retain(|d| !d.as_os_str().is_empty() && seen.insert(d.clone()));
+
+    let mut locks = vec![];
+    for dir in destinations {
+        // An unusable destination is reported by its task
+        if !dir.is_dir() && (!config.create_local_path_if_missing || std::fs::create_dir_all(&dir).is_err()) {
+            continue;
+        }
+        let lock_path = dir.join(SCAN_LOCK_NAME);
+        let mut file = match OpenOptions::new().read(true).write(true).create(true).truncate(false).open(&lock_path) {
+            Ok(file) => file,
+            Err(e) => {
+                log::warn!("Could not open scan lock {}: {}", lock_path.display(), e);
+                continue;
+            }
+        };
+        if file.try_lock_exclusive().is_err() {
+            let mut owner = String::new();
+            let _ = file.read_to_string(&mut owner);
+            let owner = owner.trim();
+            return Err(format!(
+                "{} is being synced by another instance{}",
+                dir.display(),
+                if owner.is_empty() { String::new() } else { format!(" ({})", owner) }
+            ));
+        }
+        let _ = file.set_len(0);
+        let _ = write!(file, "pid {} since {}", std::process::id(), Local::now().format("%Y-%m-%d %H:%M:%S"));
+        locks.push(ScanLock { _file: file });
+    }
+    Ok(locks)
+}
+
+enum Listing {
+    Candidates(Vec<Candidate>),
+    Failed(String),
+    Cancelled,
+}
+
+async fn list_candidates(path: PathBuf, patterns: Vec<Regex>, datetime_format: String, default_version: String, should_cancel: Arc<AtomicBool>) -> Listing {
+    let mut entries = match fs::read_dir(&path).await {
+        Ok(entries) => entries,
+        Err(e) => return Listing::Failed(format!("Failed to read {}: {}", path.display(), e)),
+    };
+
+    let mut candidates = vec![];
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if should_cancel.load(Ordering::SeqCst) {
+            return Listing::Cancelled;
+        }
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        let (datetime, version) = match parse_folder_name(&patterns, &datetime_format, &name, &default_version) {
+            Some(parsed) => {
+                log::debug!("{} matched folder pattern #{} ({})", name, parsed.pattern_index + 1, patterns[parsed.pattern_index].as_str());
+                (parsed.datetime, parsed.version)
+            },
+            None => (NaiveDateTime::MIN, String::new()),
+        };
+
+        candidates.push(Candidate {
+            path: entry.path(),
+            name,
+            version,
+            datetime,
+            mtime: entry.metadata().await.ok().and_then(|m| m.modified().ok()),
+        });
+    }
+    Listing::Candidates(candidates)
+}
+
+// Lists every VersionMatch path up front, at most `max_concurrency` at a time, so several slow
+// shares are read in parallel instead of one after another. Keyed by the task's remote_path.
+async fn list_version_paths<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
+    patterns: &[Regex],
+    should_cancel: &Arc<AtomicBool>
+) -> HashMap<String, Listing> {
+    let mut paths: Vec<String> = config.tasks.iter()
+        .filter(|t| t.enabled && matches!(t.rule, MatchRule::VersionMatch(_)))
+        .map(|t| t.remote_path.clone())
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    let gate = ConcurrencyGate::new(live_limits(app_handle, config));
+    let mut pending = tokio::task::JoinSet::new();
+    for remote_path in paths {
+        let gate = gate.clone();
+        let patterns = patterns.to_vec();
+        let datetime_format = config.datetime_format.clone();
+        let default_version = config.default_version.clone();
+        let should_cancel = should_cancel.clone();
+        pending.spawn(async move {
+            let _permit = gate.enter().await;
+            let listing = if should_cancel.load(Ordering::SeqCst) {
+                Listing::Cancelled
+            } else {
+                list_candidates(PathBuf::from(&remote_path), patterns, datetime_format, default_version, should_cancel).await
+            };
+            (remote_path, listing)
+        });
+    }
+
+    let mut listings = HashMap::new();
+    while let Some(joined) = pending.join_next().await {
+        match joined {
+            Ok((remote_path, listing)) => { listings.insert(remote_path, listing); }
+            Err(e) => log::error!("Listing task failed: {}", e),
+        }
+    }
+    listings
 }
 
 // Helper to emit logs to frontend in real-time
-fn emit_log<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, msg: String, level: &str) {
+fn emit_log<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, msg: String, level: LogLevel) {
+    emit_log_as(app_handle, msg, level, "scan");
+}
+
+fn emit_copy_log<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, msg: String, level: LogLevel) {
+    emit_log_as(app_handle, msg, level, "copy");
+}
+
+pub fn emit_system_log<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, msg: String, level: LogLevel) {
+    emit_log_as(app_handle, msg, level, "system");
+}
+
+fn emit_log_as<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, msg: String, level: LogLevel, category: &str) {
+    if !level_enabled(app_handle, level) {
+        return;
+    }
+    let shown = ui_message(app_handle, msg.clone());
+    record_log(app_handle, level, category, &shown);
     let _ = app_handle.emit("log-message", LogEvent {
-        msg: msg.clone(),
-        level: level.to_string(),
+        msg: shown,
+        level,
     });
 
     // Also write to log file
-    if let Ok(app_dir) = app_handle.path().app_data_dir() {
-         let path_buf = app_dir.clone();
-         if let Ok(_) = std::fs::create_dir_all(&path_buf) {
-             let log_path = path_buf.join("app.log");
-             if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(log_path) {
-                 let time = Local::now().format("%Y-%m-%d %H:%M:%S");
-                 let _ = writeln!(file, "[{}] [{}] {}", time, level.to_uppercase(), msg);
-             }
-         }
+    let path_buf = crate::config::app_data_dir(app_handle);
+    if let Ok(_) = std::fs::create_dir_all(&path_buf) {
+        let log_path = path_buf.join("app.log");
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(log_path) {
+            let time = Local::now().format("%Y-%m-%d %H:%M:%S");
+            let _ = writeln!(file, "[{}] [{}] {}", time, level.as_str().to_uppercase(), msg);
+        }
     }
 }
 
@@ -97,45 +658,585 @@ fn emit_progress<R: tauri::Runtime>(
     });
 }
 
-// Helper function to copy file with chunking and interruption support
-fn copy_file_chunked<P: AsRef<Path>, Q: AsRef<Path>>(
-    from: P, 
-    to: Q, 
-    should_cancel: &Arc<AtomicBool>,
-    is_paused: &Arc<AtomicBool>,
-    on_progress: &mut dyn FnMut(u64) // bytes copied delta
-) -> Result<u64, String> {
-    let mut file_in = std::fs::File::open(from).map_err(|e| e.to_string())?;
-    let mut file_out = std::fs::File::create(to).map_err(|e| e.to_string())?;
-    
-    let mut buffer = [0u8; 64 * 1024]; // 64KB buffer
-    let mut total_copied = 0;
-    
-    loop {
-        // Check cancel
-        if should_cancel.load(Ordering::SeqCst) {
-            return Err("Cancelled by user".to_string());
+// What happened while copying a single file, for the caller to log
+#[derive(Debug, Default)]
+struct CopyOutcome {
+    readonly_cleared: bool,
+    ownership_error: Option<String>,
+    reflinked: bool,
+}
+
+// Only warn once per run of the app when ownership can't be preserved
+static OWNERSHIP_WARNED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+fn copy_ownership(from: &std::fs::File, to: &std::fs::File) -> std::io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = from.metadata()?;
+    std::os::unix::fs::fchown(to, Some(meta.uid()), Some(meta.gid()))
+}
+
+#[cfg(not(unix))]
+fn copy_ownership(_from: &std::fs::File, _to: &std::fs::File) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "ownership is not supported on this platform"))
+}
+
+// Compared case-insensitively; dotfiles such as .DS_Store are caught by the leading dot
+const JUNK_FILE_NAMES: &[&str] = &["thumbs.db", "ehthumbs.db", "desktop.ini"];
+
+fn is_hidden_entry(name: &str, metadata: &std::fs::Metadata) -> bool {
+    name.starts_with('.') || JUNK_FILE_NAMES.contains(&name.to_lowercase().as_str()) || has_hidden_attribute(metadata)
+}
+
+#[cfg(windows)]
+fn has_hidden_attribute(metadata: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    const FILE_ATTRIBUTE_SYSTEM: u32 = 0x4;
+    metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0
+}
+
+#[cfg(not(windows))]
+fn has_hidden_attribute(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+// Creates the destination, clearing a read-only attribute on an existing file if allowed
+fn create_destination(to: &Path, force_readonly: bool) -> Result<(std::fs::File, bool), String> {
+    match std::fs::File::create(to) {
+        Ok(file) => Ok((file, false)),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied && force_readonly => {
+            let mut perms = std::fs::metadata(to).map_err(|_| e.to_string())?.permissions();
+            if !perms.readonly() {
+                return Err(e.to_string());
+            }
+            #[allow(clippy::permissions_set_readonly_false)]
+            perms.set_readonly(false);
+            std::fs::set_permissions(to, perms).map_err(|e| e.to_string())?;
+            let file = std::fs::File::create(to).map_err(|e| e.to_string())?;
+            Ok((file, true))
+        },
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Per-file copy behaviour derived from the config
+#[derive(Debug, Clone, Default)]
+struct CopyOptions {
+    force_readonly: bool,
+    preserve_ownership: bool,
+    use_reflink: bool,
+    line_ending: Option<LineEnding>, // Set only for files matching line_ending_extensions
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    fn from_config(value: Option<&str>) -> Option<Self> {
+        match value.map(|v| v.to_lowercase()).as_deref() {
+            Some("lf") => Some(LineEnding::Lf),
+            Some("crlf") => Some(LineEnding::Crlf),
+            _ => None,
+        }
+    }
+}
+
+// Streaming CRLF/LF conversion; remembers a trailing CR so pairs split across chunks survive
+struct LineEndingConverter {
+    target: LineEnding,
+    pending_cr: bool,
+}
+
+impl LineEndingConverter {
+    fn convert(&mut self, chunk: &[u8], out: &mut Vec<u8>) {
+        out.clear();
+        for &b in chunk {
+            match (self.target, b) {
+                (LineEnding::Lf, b'\n') => {
+                    self.pending_cr = false;
+                    out.push(b'\n');
+                },
+                (LineEnding::Lf, b'\r') => {
+                    if self.pending_cr {
+                        out.push(b'\r');
+                    }
+                    self.pending_cr = true;
+                },
+                (LineEnding::Lf, _) => {
+                    if self.pending_cr {
+                        out.push(b'\r');
+                        self.pending_cr = false;
+                    }
+                    out.push(b);
+                },
+                (LineEnding::Crlf, b'\n') => {
+                    if !self.pending_cr {
+                        out.push(b'\r');
+                    }
+                    out.push(b'\n');
+                    self.pending_cr = false;
+                },
+                (LineEnding::Crlf, _) => {
+                    out.push(b);
+                    self.pending_cr = b == b'\r';
+                },
+            }
+        }
+    }
+
+    fn finish(&mut self, out: &mut Vec<u8>) {
+        out.clear();
+        if self.target == LineEnding::Lf && self.pending_cr {
+            out.push(b'\r');
+        }
+        self.pending_cr = false;
+    }
+}
+
+// Same heuristic as git: a NUL byte near the start means binary
+fn looks_binary(head: &[u8]) -> bool {
+    head.iter().take(8000).any(|&b| b == 0)
+}
+
+// Helper function to copy file with chunking and interruption support
+fn copy_file_chunked<P: AsRef<Path>, Q: AsRef<Path>>(
+    from: P, 
+    to: Q, 
+    options: &CopyOptions,
+    should_cancel: &Arc<AtomicBool>,
+    is_paused: &Arc<AtomicBool>,
+    on_progress: &mut dyn FnMut(u64) // bytes copied delta
+) -> Result<CopyOutcome, String> {
+    // Copy-on-write clone when the filesystem supports it (same volume on Btrfs/XFS/APFS/ReFS).
+    // Needs a fresh destination and byte-identical content, so conversions use the normal path.
+    if options.use_reflink && options.line_ending.is_none() && !to.as_ref().exists()
+        && reflink_copy::reflink(from.as_ref(), to.as_ref()).is_ok() {
+        // No bytes went through on_progress (and its throttle); the caller accounts for the size
+        let file_in = std::fs::File::open(from).map_err(|e| e.to_string())?;
+        let ownership_error = if options.preserve_ownership {
+            std::fs::File::open(to.as_ref())
+                .and_then(|file_out| copy_ownership(&file_in, &file_out))
+                .err()
+                .map(|e| e.to_string())
+        } else {
+            None
+        };
+        return Ok(CopyOutcome { readonly_cleared: false, ownership_error, reflinked: true });
+    }
+
+    let mut file_in = std::fs::File::open(from).map_err(|e| e.to_string())?;
+    let (mut file_out, readonly_cleared) = create_destination(to.as_ref(), options.force_readonly)?;
+    
+    let mut buffer = [0u8; 64 * 1024]; // 64KB buffer
+    let mut converter = options.line_ending.map(|target| LineEndingConverter { target, pending_cr: false });
+    let mut converted = Vec::new();
+    let mut first_chunk = true;
+    
+    loop {
+        // Check cancel
+        if should_cancel.load(Ordering::SeqCst) {
+            return Err("Cancelled by user".to_string());
+        }
+        
+        // Check pause
+        while is_paused.load(Ordering::SeqCst) {
+            if should_cancel.load(Ordering::SeqCst) {
+                return Err("Cancelled by user".to_string());
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        
+        let n = file_in.read(&mut buffer).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break; // EOF
+        }
+        
+        if first_chunk {
+            first_chunk = false;
+            if converter.is_some() && looks_binary(&buffer[..n]) {
+                converter = None;
+            }
+        }
+        
+        match converter.as_mut() {
+            Some(c) => {
+                c.convert(&buffer[..n], &mut converted);
+                file_out.write_all(&converted).map_err(|e| e.to_string())?;
+            },
+            None => file_out.write_all(&buffer[..n]).map_err(|e| e.to_string())?,
+        }
+        on_progress(n as u64);
+    }
+    if let Some(c) = converter.as_mut() {
+        c.finish(&mut converted);
+        file_out.write_all(&converted).map_err(|e| e.to_string())?;
+    }
+    
+    // Needs root (or CAP_CHOWN) to hand files to another user; failure leaves the copy intact
+    let ownership_error = if options.preserve_ownership {
+        copy_ownership(&file_in, &file_out).err().map(|e| e.to_string())
+    } else {
+        None
+    };
+
+    Ok(CopyOutcome { readonly_cleared, ownership_error, reflinked: false })
+}
+
+const COPY_TIMED_OUT: &str = "Timed out";
+
+enum CopyMessage {
+    Progress(u64),
+    Done(Result<CopyOutcome, String>),
+}
+
+// Worker copies land next to the destination under a name no other copy uses
+fn worker_temp_path(to: &Path) -> PathBuf {
+    let name = to.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    to.with_file_name(format!(".{}.{}.copying", name, &uuid::Uuid::new_v4().simple().to_string()[..8]))
+}
+
+// Moves a finished worker copy over the destination. A read-only destination is refused like
+// create_destination would, unless force_readonly allows clearing the attribute first.
+fn replace_destination(temp: &Path, to: &Path, force_readonly: bool) -> Result<bool, String> {
+    let mut readonly_cleared = false;
+    if let Ok(metadata) = std::fs::symlink_metadata(to) {
+        let mut perms = metadata.permissions();
+        if metadata.is_file() && perms.readonly() {
+            if !force_readonly {
+                return Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied).to_string());
+            }
+            #[allow(clippy::permissions_set_readonly_false)]
+            perms.set_readonly(false);
+            std::fs::set_permissions(to, perms).map_err(|e| e.to_string())?;
+            readonly_cleared = true;
+        }
+    }
+    std::fs::rename(temp, to).map_err(|e| e.to_string())?;
+    Ok(readonly_cleared)
+}
+
+// Runs copy_file_chunked on a worker thread so a read that hangs on a dying share can be given
+// up on after `timeout` without progress. The worker writes to its own temp file and only
+// renames it onto `to` when it finishes in time, so a stuck worker that gets unstuck later can
+// only ever remove its own partial file, never a copy a later scan has made since.
+fn copy_file_with_watchdog(
+    from: &Path,
+    to: &Path,
+    options: &CopyOptions,
+    should_cancel: &Arc<AtomicBool>,
+    is_paused: &Arc<AtomicBool>,
+    timeout: std::time::Duration,
+    on_progress: &mut dyn FnMut(u64)
+) -> Result<CopyOutcome, String> {
+    let abort = Arc::new(AtomicBool::new(false));
+    let abandoned = Arc::new(AtomicBool::new(false));
+    // Unbuffered, so the worker only reads on once each chunk is accounted for (and throttled)
+    let (tx, rx) = std::sync::mpsc::sync_channel::<CopyMessage>(0);
+    let temp = worker_temp_path(to);
+    let worker = {
+        let (from, to, temp, options) = (from.to_path_buf(), to.to_path_buf(), temp.clone(), options.clone());
+        let (abort, abandoned, is_paused) = (abort.clone(), abandoned.clone(), is_paused.clone());
+        std::thread::spawn(move || {
+            let result = copy_file_chunked(&from, &temp, &options, &abort, &is_paused, &mut |delta| {
+                let _ = tx.send(CopyMessage::Progress(delta));
+            });
+            if abandoned.load(Ordering::SeqCst) {
+                let _ = std::fs::remove_file(&temp);
+                return;
+            }
+            let result = result.and_then(|outcome| {
+                let readonly_cleared = replace_destination(&temp, &to, options.force_readonly)?;
+                Ok(CopyOutcome { readonly_cleared, ..outcome })
+            });
+            if result.is_err() {
+                let _ = std::fs::remove_file(&temp);
+            }
+            let _ = tx.send(CopyMessage::Done(result));
+        })
+    };
+
+    let mut last_progress = Instant::now();
+    loop {
+        if should_cancel.load(Ordering::SeqCst) {
+            abandoned.store(true, Ordering::SeqCst);
+            abort.store(true, Ordering::SeqCst);
+            return Err("Cancelled by user".to_string());
+        }
+        match rx.recv_timeout(std::time::Duration::from_millis(100)) {
+            Ok(CopyMessage::Progress(delta)) => {
+                last_progress = Instant::now();
+                on_progress(delta);
+            }
+            Ok(CopyMessage::Done(result)) => {
+                let _ = worker.join();
+                return result;
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                return Err("Copy worker stopped unexpectedly".to_string());
+            }
+            // A paused copy isn't stuck
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) if is_paused.load(Ordering::SeqCst) => {
+                last_progress = Instant::now();
+            }
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                if last_progress.elapsed() >= timeout {
+                    abandoned.store(true, Ordering::SeqCst);
+                    abort.store(true, Ordering::SeqCst);
+                    // Usually still open by the worker on Windows; it retries once the read returns
+                    let _ = std::fs::remove_file(&temp);
+                    return Err(format!("{} after {}s without progress", COPY_TIMED_OUT, timeout.as_secs()));
+                }
+            }
+        }
+    }
+}
+
+// Set for every post-copy command; `${target}` expands to a quoted reference to it, so the
+// path is never parsed as shell syntax
+const POST_COPY_TARGET_ENV: &str = "FILE_SYNC_TARGET";
+
+/// Runs each `post_copy_commands` entry locally with `${target}` set to the copied folder.
+/// Output goes to the log; a command that fails or exceeds post_copy_command_timeout_secs is
+/// killed and recorded as a scan error, and the next one (and the deploy) still runs. Cancelling
+/// the scan kills the running command.
+fn run_post_copy_commands<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
+    target: &Path,
+    should_cancel: &Arc<AtomicBool>,
+    report: &ScanReport
+) -> Result<(), SyncError> {
+    let timeout = (config.post_copy_command_timeout_secs > 0).then(|| std::time::Duration::from_secs(config.post_copy_command_timeout_secs));
+    for template in config.post_copy_commands.iter().filter(|c| !c.trim().is_empty()) {
+        let command = template.replace("${target}", TARGET_REFERENCE);
+        emit_copy_log(app_handle, format!("Running post-copy command: {} ({} = {})", command, POST_COPY_TARGET_ENV, target.display()), LogLevel::Info);
+        let mut child = match shell_command(&command)
+            .env(POST_COPY_TARGET_ENV, target)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                let msg = format!("Post-copy command '{}' could not start: {}", command, e);
+                emit_copy_log(app_handle, msg.clone(), LogLevel::Error);
+                report.error(msg);
+                continue;
+            }
+        };
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Ok(status),
+                Ok(None) => {}
+                Err(e) => break Err(e.to_string()),
+            }
+            if should_cancel.load(Ordering::SeqCst) {
+                let _ = child.kill();
+                let _ = child.wait();
+                emit_copy_log(app_handle, format!("Post-copy command '{}' killed: scan cancelled", command), LogLevel::Warn);
+                return Err(SyncError::Cancelled);
+            }
+            if timeout.is_some_and(|t| started.elapsed() >= t) {
+                let _ = child.kill();
+                let _ = child.wait();
+                break Err(format!("timed out after {}s", config.post_copy_command_timeout_secs));
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        };
+
+        // A killed command's children may still hold the pipes, so output is only awaited after a normal exit
+        if status.is_ok() {
+            let stdout = stdout.join().unwrap_or_default();
+            if !stdout.trim().is_empty() {
+                emit_copy_log(app_handle, stdout.trim_end().to_string(), LogLevel::Info);
+            }
+            let stderr = stderr.join().unwrap_or_default();
+            if !stderr.trim().is_empty() {
+                emit_copy_log(app_handle, stderr.trim_end().to_string(), LogLevel::Warn);
+            }
+        }
+        let failure = match status {
+            Ok(status) if status.success() => None,
+            Ok(status) => Some(status.code().map_or_else(|| "failed with a signal".to_string(), |c| format!("failed with exit code {}", c))),
+            Err(e) => Some(e),
+        };
+        if let Some(failure) = failure {
+            let msg = format!("Post-copy command '{}' {}", command, failure);
+            emit_copy_log(app_handle, msg.clone(), LogLevel::Error);
+            report.error(msg);
+        }
+    }
+    Ok(())
+}
+
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut bytes = vec![];
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        String::from_utf8_lossy(&bytes).to_string()
+    })
+}
+
+#[cfg(windows)]
+const TARGET_REFERENCE: &str = "\"%FILE_SYNC_TARGET%\"";
+
+#[cfg(not(windows))]
+const TARGET_REFERENCE: &str = "\"$FILE_SYNC_TARGET\"";
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> std::process::Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let mut cmd = std::process::Command::new("cmd");
+    cmd.arg("/C").arg(command).creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+#[cfg(not(windows))]
+fn shell_command(command: &str) -> std::process::Command {
+    let mut cmd = std::process::Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+// Gives network drives time to commit what was just written so the deploy doesn't read
+// stale data: optional fsync of each copied file, then post_copy_delay_ms (cancellable).
+fn settle_before_deploy<R: tauri::Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    config: &AppConfig,
+    copied_paths: &[PathBuf],
+    should_cancel: &Arc<AtomicBool>
+) -> Result<(), SyncError> {
+    if config.fsync_before_deploy {
+        for path in copied_paths {
+            if let Err(e) = std::fs::OpenOptions::new().write(true).open(path).and_then(|f| f.sync_all()) {
+                emit_copy_log(app_handle, format!("Failed to flush {}: {}", path.display(), e), LogLevel::Warn);
+            }
         }
-        
-        // Check pause
-        while is_paused.load(Ordering::SeqCst) {
+    }
+
+    if config.post_copy_delay_ms > 0 {
+        emit_copy_log(app_handle, format!("Waiting {} ms before deploy", config.post_copy_delay_ms), LogLevel::Info);
+        let deadline = Instant::now() + std::time::Duration::from_millis(config.post_copy_delay_ms);
+        while Instant::now() < deadline {
             if should_cancel.load(Ordering::SeqCst) {
-                return Err("Cancelled by user".to_string());
+                return Err(SyncError::Cancelled);
             }
-            std::thread::sleep(std::time::Duration::from_millis(100));
+            std::thread::sleep(std::time::Duration::from_millis(100).min(deadline - Instant::now()));
         }
-        
-        let n = file_in.read(&mut buffer).map_err(|e| e.to_string())?;
-        if n == 0 {
-            break; // EOF
+    }
+    Ok(())
+}
+
+// Set when the drive letter / mount root of `path` itself is gone, e.g. an unplugged USB disk
+fn missing_drive_error(path: &Path) -> Option<String> {
+    let root: PathBuf = path.components()
+        .take_while(|c| matches!(c, Component::Prefix(_) | Component::RootDir))
+        .collect();
+    if root.as_os_str().is_empty() || root.exists() {
+        return None;
+    }
+    Some(format!("Drive {} is not available (disconnected or unmounted?), can't use {}", root.display(), path.display()))
+}
+
+// Tells a missing drive apart from a permission problem, so the user knows whether to plug
+// something in or fix the folder's rights
+fn describe_create_error(path: &Path, e: &std::io::Error) -> String {
+    if let Some(err) = missing_drive_error(path) {
+        return err;
+    }
+    match e.kind() {
+        std::io::ErrorKind::PermissionDenied => format!("Permission denied creating {}: check that this user may write to its parent folder", path.display()),
+        _ if path.ancestors().skip(1).any(|a| a.is_file()) => format!("Can't create {}: part of the path is a file, not a folder", path.display()),
+        _ => format!("Failed to create local directory {}: {}", path.display(), e),
+    }
+}
+
+// Makes sure a task's destination folder is usable before anything is copied into it
+fn prepare_local_path(path: &Path, create_if_missing: bool) -> Result<(), String> {
+    if path.is_dir() {
+        return Ok(());
+    }
+    if path.exists() {
+        return Err(format!("Destination {} is a file, not a folder", path.display()));
+    }
+    if let Some(err) = missing_drive_error(path) {
+        return Err(err);
+    }
+    if !create_if_missing {
+        return Err(format!("Destination {} does not exist and create_local_path_if_missing is off", path.display()));
+    }
+    std::fs::create_dir_all(path).map_err(|e| describe_create_error(path, &e))
+}
+
+// canonicalize for a path that may not exist yet: resolves the deepest existing ancestor and
+// appends the missing components as given
+fn canonicalize_missing(path: &Path) -> std::io::Result<PathBuf> {
+    let mut existing = path;
+    let mut missing = vec![];
+    loop {
+        match std::fs::canonicalize(existing) {
+            Ok(resolved) => return Ok(missing.iter().rev().fold(resolved, |acc, name| acc.join(name))),
+            Err(e) => match (existing.parent(), existing.file_name()) {
+                (Some(parent), Some(name)) => {
+                    missing.push(name);
+                    existing = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+                }
+                _ => return Err(e),
+            },
         }
-        
-        file_out.write_all(&buffer[..n]).map_err(|e| e.to_string())?;
-        total_copied += n as u64;
-        on_progress(n as u64);
     }
-    
-    Ok(total_copied)
+}
+
+// Errors when the destination is the source folder or somewhere below it. Both sides are
+// canonicalized so mapped drives, `..` and symlinks can't hide the overlap.
+fn check_not_inside_source(source_path: &Path, target_parent_path: &Path, folder_name: &str) -> Result<(), String> {
+    let source = std::fs::canonicalize(source_path)
+        .map_err(|e| format!("Failed to resolve source {}: {}", source_path.display(), e))?;
+    let target = canonicalize_missing(target_parent_path)
+        .map_err(|e| format!("Failed to resolve destination {}: {}", target_parent_path.display(), e))?
+        .join(folder_name);
+    if target.starts_with(&source) {
+        return Err(format!(
+            "Refusing to copy {} into {}: the destination is inside the source. Check local_path.",
+            source.display(), target.display()
+        ));
+    }
+    Ok(())
+}
+
+fn ends_with_any(name: &str, suffixes: &[String]) -> bool {
+    suffixes.iter().any(|suffix| name.ends_with(suffix.as_str()))
+}
+
+// Copies get a fresh mtime, so a source newer than its copy was modified after it was taken.
+// Line-ending conversion changes the size, so converted files are judged by mtime alone.
+fn differs_from_destination(source: &std::fs::Metadata, dst: &Path, converted: bool) -> bool {
+    let Ok(existing) = std::fs::metadata(dst) else { return true };
+    if !converted && existing.len() != source.len() {
+        return true;
+    }
+    match (source.modified(), existing.modified()) {
+        (Ok(source_modified), Ok(copy_modified)) => source_modified > copy_modified,
+        _ => false,
+    }
+}
+
+// Size and mtime of a source file, compared by detect_source_drift
+fn file_signature(path: &Path) -> Option<(u64, Option<std::time::SystemTime>)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.len(), metadata.modified().ok()))
 }
 
 // Extracted copy logic to reuse across different matching rules
@@ -147,24 +1248,48 @@ async fn perform_copy<R: tauri::Runtime>(
     config: &AppConfig,
     should_cancel: Arc<AtomicBool>,
     is_paused: Arc<AtomicBool>,
-    result: &mut ScanResult
+    report: &ScanReport
 ) {
     let target_full_path = target_parent_path.join(&folder_name);
+
+    // The build system writes the sentinel last, so a folder without it is still being filled
+    if let Some(sentinel) = config.completion_sentinel.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        if !source_path.join(sentinel).is_file() {
+            emit_copy_log(app_handle, format!("Skipping {}: completion marker {} not present yet", folder_name, sentinel), LogLevel::Info);
+            report.overall.settle(&target_full_path, 0);
+            return;
+        }
+    }
     
-    emit_log(app_handle, format!("Target local directory: {}", target_full_path.display()), "info");
+    emit_copy_log(app_handle, format!("Target local directory: {}", target_full_path.display()), LogLevel::Info);
 
     // Check if target directory exists, but don't skip entire copy - check for new files
     if target_full_path.exists() {
-         emit_log(app_handle, format!("Target directory {} exists. Checking for new files...", target_full_path.display()), "info");
+         if config.sync_mode == SyncMode::SkipExisting {
+             emit_copy_log(app_handle, format!("Skipped (Exists): {}", target_full_path.display()), LogLevel::Info);
+             report.overall.settle(&target_full_path, 0);
+             return;
+         }
+         emit_copy_log(app_handle, format!("Target directory {} exists. Checking for new or changed files...", target_full_path.display()), LogLevel::Info);
     } else {
-         emit_log(app_handle, format!("Starting copy: {} -> {}", source_path.display(), target_parent_path.display()), "info");
+         emit_copy_log(app_handle, format!("Starting copy: {} -> {}", source_path.display(), target_parent_path.display()), LogLevel::Info);
     }
     
+    // A local_path inside the share would make the scanner copy into its own source.
+    // Checked before anything is created, so a bad local_path leaves no folders in the share.
+    if let Err(err_msg) = check_not_inside_source(&source_path, target_parent_path, &folder_name) {
+        emit_copy_log(app_handle, err_msg.clone(), LogLevel::Error);
+        report.error(err_msg);
+        report.failed(folder_name);
+        return;
+    }
+
     // Ensure parent dir exists
     if let Err(e) = fs::create_dir_all(target_parent_path).await {
-        let err_msg = format!("Failed to create local directory {}: {}", target_parent_path.display(), e);
-        emit_log(app_handle, err_msg.clone(), "error");
-        result.errors.push(err_msg);
+        let err_msg = describe_create_error(target_parent_path, &e);
+        emit_copy_log(app_handle, err_msg.clone(), LogLevel::Error);
+        report.error(err_msg);
+        report.failed(folder_name);
         return;
     }
 
@@ -174,27 +1299,71 @@ async fn perform_copy<R: tauri::Runtime>(
     let target_full_path_clone = target_full_path.clone();
     
     // Clone config for closure
-    let extensions = config.file_extensions.clone();
+    let extensions = crate::config::normalize_extensions(&config.file_extensions).extensions;
     let includes = config.filename_includes.clone();
+    let excludes: Vec<glob::Pattern> = config.exclude_patterns.iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .filter_map(|p| match glob::Pattern::new(p) {
+            Ok(pattern) => Some(pattern),
+            Err(e) => {
+                emit_copy_log(app_handle, format!("Ignoring invalid exclude pattern {}: {}", p, e), LogLevel::Warn);
+                None
+            }
+        })
+        .collect();
+    let keep_descriptor = config.respect_folder_descriptor;
+    let skip_hidden = config.skip_hidden_files;
+    let quiet = config.quiet_mode;
+    let copy_order = config.copy_order.clone();
+    let copy_options = CopyOptions {
+        force_readonly: config.force_overwrite_readonly,
+        preserve_ownership: config.preserve_ownership,
+        use_reflink: config.use_reflink,
+        line_ending: None,
+    };
+    let line_ending = LineEnding::from_config(config.normalize_line_endings.as_deref());
+    let line_ending_extensions: Vec<String> = crate::config::normalize_extensions(&config.line_ending_extensions).extensions
+        .iter()
+        .map(|e| format!(".{}", e))
+        .collect();
     let config_clone = config.clone();
     let should_cancel_clone = should_cancel.clone();
     let is_paused_clone = is_paused.clone();
+    let deploy_ms = Arc::new(AtomicU64::new(0));
+    let deploy_ms_clone = deploy_ms.clone();
+    let copy_started = Instant::now();
+    let run_id = report.run_id();
+    // Every entry of this attempt reuses one id, so a later COPY_* line replaces the earlier one
+    // when the history is read instead of adding a fragment
+    let history_id = uuid::Uuid::new_v4().to_string();
+    let overall = report.overall.clone();
+    let report_overall = report.overall.clone();
+    // History tells moves apart from copies since the source is gone afterwards
+    let move_files = config.move_instead_of_copy;
+    let detect_drift = config.detect_source_drift;
+    let sync_mode = config.sync_mode;
+    let per_file_timeout = (config.per_file_timeout_secs > 0).then(|| std::time::Duration::from_secs(config.per_file_timeout_secs));
+    let file_errors = report.clone();
+    let (action, doing, done) = if move_files { ("MOVE", "moving", "moved") } else { ("COPY", "copying", "copied") };
 
-    let copy_task = tauri::async_runtime::spawn_blocking(move || {
+    let copy_task = tauri::async_runtime::spawn_blocking(move || -> Result<u64, SyncError> {
         let handle = app_handle_clone;
         
         // Log START event to history
         add_history_entry(&handle, HistoryEntry {
-            id: uuid::Uuid::new_v4().to_string(),
+            id: history_id.clone(),
             timestamp: Local::now().to_rfc3339(),
-            action_type: "COPY_STARTED".to_string(),
-            description: format!("Started copying {}", folder_name_clone),
+            action_type: format!("{}_STARTED", action),
+            description: format!("Started {} {}", doing, folder_name_clone),
             folder_name: folder_name_clone.clone(),
             source_path: source_path_clone.to_string_lossy().to_string(),
             target_path: target_full_path_clone.to_string_lossy().to_string(),
             copied_files_count: 0,
             total_size: 0,
             files: vec![],
+            run_id: run_id.clone(),
+            command_results: vec![],
         });
 
         let start_time = Instant::now();
@@ -205,9 +1374,18 @@ async fn perform_copy<R: tauri::Runtime>(
         let remote_path_display = source_path_clone.to_string_lossy().to_string();
         
         // Helper for speed/eta
+        let mut overall_reported = 0;
         let mut update_stats = |copied: u64, total: u64| {
             let now = Instant::now();
             if now.duration_since(last_emit_time).as_millis() > 500 || copied == total {
+                last_emit_time = now;
+                overall.add(copied.saturating_sub(overall_reported));
+                overall_reported = copied;
+                overall.emit(&handle);
+                record_sample(&handle, &folder_name_clone, start_time.elapsed().as_millis() as u64, copied, total);
+                if quiet {
+                    return;
+                }
                 let elapsed = start_time.elapsed().as_secs_f64();
                 let speed = if elapsed > 0.0 {
                     (copied as f64 / elapsed) as u64
@@ -232,27 +1410,56 @@ async fn perform_copy<R: tauri::Runtime>(
                     &local_path_display,
                     &remote_path_display
                 );
-                last_emit_time = now;
             }
         };
         
         // Just test access to source dir
         if let Err(e) = std::fs::read_dir(&source_path_clone) {
-             let e = e.to_string(); 
-             emit_log(&handle, format!("Failed to access source dir: {}", e), "error");
-             return Err(fs_extra::error::Error::new(fs_extra::error::ErrorKind::Other, &e));
+             return Err(SyncError::Io(format!("Failed to access source dir: {}", e)));
         }
         
         // Collect files with filtering (Iterative)
         let mut filtered_files = Vec::new();
         let mut total_filtered_bytes = 0;
         
+        let mut hidden_skipped = 0;
+        let mut unchanged_skipped = 0;
+        let mut links_skipped = 0;
+        let mut links = SymlinkGuard::new(&source_path_clone, config_clone.follow_symlinks);
         let mut dirs_to_visit = vec![source_path_clone.clone()];
         while let Some(current_dir) = dirs_to_visit.pop() {
              if let Ok(entries) = std::fs::read_dir(&current_dir) {
                  for entry in entries.flatten() {
                      let path = entry.path();
-                     if path.is_dir() {
+                     // Forward slashes so "node_modules/**" works the same on Windows
+                     let rel_str = path.strip_prefix(&source_path_clone).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+                     if excludes.iter().any(|p| p.matches(&rel_str)) {
+                         log::debug!("Excluded {} in {}", rel_str, folder_name_clone);
+                         continue;
+                     }
+                     if skip_hidden {
+                         let name = entry.file_name().to_string_lossy().to_string();
+                         let is_descriptor = keep_descriptor && name == FOLDER_DESCRIPTOR_NAME;
+                         if !is_descriptor && entry.metadata().is_ok_and(|meta| is_hidden_entry(&name, &meta)) {
+                             hidden_skipped += 1;
+                             continue;
+                         }
+                     }
+                     let Ok(file_type) = entry.file_type() else { continue };
+                     let kind = links.classify(&path, file_type);
+                     match kind {
+                         WalkEntry::Link => {
+                             log::debug!("Skipped symlink {}", path.display());
+                             links_skipped += 1;
+                             continue;
+                         }
+                         WalkEntry::Cycle => {
+                             emit_copy_log(&handle, format!("Skipped {}: it links back to a folder already copied", path.display()), LogLevel::Warn);
+                             continue;
+                         }
+                         WalkEntry::Dir | WalkEntry::File => {}
+                     }
+                     if kind == WalkEntry::Dir {
                          dirs_to_visit.push(path);
                      } else {
                          // File Check
@@ -291,15 +1498,22 @@ async fn perform_copy<R: tauri::Runtime>(
                              }
                          }
                          
-                         if ext_match && inc_match {
+                         // The deploy descriptor must survive the filters so deploy can read it
+                         let is_descriptor = keep_descriptor && file_name == FOLDER_DESCRIPTOR_NAME;
+
+                         if (ext_match && inc_match) || is_descriptor {
                             // Check if file already exists locally
                             let rel_path = path.strip_prefix(&source_path_clone).unwrap_or(&path);
                             let dst = target_full_path_clone.join(rel_path);
                             
-                            if !dst.exists() {
-                                if let Ok(meta) = entry.metadata() {
-                                    filtered_files.push((path, meta.len()));
+                            // Follows a symlinked file to the file it points at
+                            if let Ok(meta) = std::fs::metadata(&path) {
+                                let converted = line_ending.is_some() && ends_with_any(&file_name.to_lowercase(), &line_ending_extensions);
+                                if sync_mode == SyncMode::Overwrite || differs_from_destination(&meta, &dst, converted) {
                                     total_filtered_bytes += meta.len();
+                                    filtered_files.push((path, meta.len(), meta.modified().ok()));
+                                } else {
+                                    unchanged_skipped += 1;
                                 }
                             }
                         }
@@ -308,36 +1522,97 @@ async fn perform_copy<R: tauri::Runtime>(
              }
         }
         
+        report_overall.settle(&target_full_path_clone, total_filtered_bytes);
+
+        if hidden_skipped > 0 {
+            emit_copy_log(&handle, format!("Skipped {} hidden/system entries in {}", hidden_skipped, folder_name_clone), LogLevel::Info);
+        }
+
+        if links_skipped > 0 {
+            emit_copy_log(&handle, format!("Skipped {} symlink(s) in {} (follow_symlinks is off)", links_skipped, folder_name_clone), LogLevel::Info);
+        }
+
+        if unchanged_skipped > 0 {
+            emit_copy_log(&handle, format!("Skipped {} unchanged file(s) in {}", unchanged_skipped, folder_name_clone), LogLevel::Info);
+        }
+
         if filtered_files.is_empty() {
-            emit_log(&handle, format!("No files found to copy in {}", folder_name_clone), "warn");
+            if unchanged_skipped > 0 {
+                emit_copy_log(&handle, format!("{} is already up to date", folder_name_clone), LogLevel::Info);
+            } else {
+                emit_copy_log(&handle, format!("No files found to copy in {}", folder_name_clone), LogLevel::Warn);
+            }
             return Ok(0);
         }
         
-        emit_log(&handle, format!("Found {} files ({}) to copy.", filtered_files.len(), total_filtered_bytes), "info");
+        emit_copy_log(&handle, format!("Found {} files ({}) to copy.", filtered_files.len(), total_filtered_bytes), LogLevel::Info);
+
+        // Better to skip now than to run out of room halfway and leave a partial folder behind
+        let volume = target_full_path_clone.parent().unwrap_or(&target_full_path_clone);
+        match fs2::available_space(volume) {
+            Ok(available) if available < total_filtered_bytes => {
+                let msg = format!(
+                    "Not enough disk space for {}: needs {} bytes, {} has {} bytes free",
+                    folder_name_clone, total_filtered_bytes, volume.display(), available
+                );
+                add_history_entry(&handle, HistoryEntry {
+                    id: history_id.clone(),
+                    timestamp: Local::now().to_rfc3339(),
+                    action_type: format!("{}_SKIPPED", action),
+                    description: msg.clone(),
+                    folder_name: folder_name_clone.clone(),
+                    source_path: source_path_clone.to_string_lossy().to_string(),
+                    target_path: target_full_path_clone.to_string_lossy().to_string(),
+                    copied_files_count: 0,
+                    total_size: total_filtered_bytes,
+                    files: vec![],
+                    run_id: run_id.clone(),
+                    command_results: vec![],
+                });
+                return Err(SyncError::Io(msg));
+            }
+            Ok(_) => {}
+            Err(e) => emit_copy_log(&handle, format!("Could not check free space on {}: {}", volume.display(), e), LogLevel::Warn),
+        }
+
+        // "walk" keeps directory-walk order
+        match copy_order.as_str() {
+            "name" => filtered_files.sort_by(|a, b| a.0.cmp(&b.0)),
+            "size_asc" => filtered_files.sort_by_key(|f| f.1),
+            "size_desc" => filtered_files.sort_by_key(|f| std::cmp::Reverse(f.1)),
+            _ => {}
+        }
         
         // Create target directory structure and Copy
         let mut copied_bytes_total = 0;
         let mut copied_files_list = Vec::new();
+        let mut copied_paths = Vec::new();
+        let mut reflinked_files = 0;
+        let mut renamed_files = 0;
+        let mut drifted_files = 0;
+        let mut throttle = Throttle::new(live_limits(&handle, &config_clone));
         
-        for (src, _size) in filtered_files {
+        for (src, mut size, modified) in filtered_files {
             // Check cancel before starting file
              if should_cancel_clone.load(Ordering::SeqCst) {
                  // Log partial
                  if !copied_files_list.is_empty() {
                      add_history_entry(&handle, HistoryEntry {
-                         id: uuid::Uuid::new_v4().to_string(),
+                         id: history_id.clone(),
                          timestamp: Local::now().to_rfc3339(),
-                         action_type: "COPY_CANCELLED".to_string(),
-                         description: format!("Cancelled copying {}", folder_name_clone),
+                         action_type: format!("{}_CANCELLED", action),
+                         description: format!("Cancelled {} {}", doing, folder_name_clone),
                          folder_name: format!("{} (Cancelled)", folder_name_clone),
                          source_path: source_path_clone.to_string_lossy().to_string(),
                          target_path: target_full_path_clone.to_string_lossy().to_string(),
                          copied_files_count: copied_files_list.len(),
                          total_size: copied_bytes_total,
                          files: copied_files_list.clone(),
+                         run_id: run_id.clone(),
+                         command_results: vec![],
                      });
                  }
-                 return Err(fs_extra::error::Error::new(fs_extra::error::ErrorKind::Interrupted, "Cancelled by user"));
+                 return Err(SyncError::Cancelled);
              }
             
              // Calculate relative path
@@ -351,100 +1626,308 @@ async fn perform_copy<R: tauri::Runtime>(
              
              let file_name_display = src.file_name().unwrap_or_default().to_string_lossy().to_string();
 
-             // Copy with chunking
-             let copy_res = copy_file_chunked(
-                 &src, 
-                 &dst, 
-                 &should_cancel_clone, 
-                 &is_paused_clone,
-                 &mut |delta| {
-                     copied_bytes_total += delta;
-                     update_stats(copied_bytes_total, total_filtered_bytes);
+             // The producer may still be writing: copy what is there now, and check again afterwards
+             let signature = if detect_drift { file_signature(&src) } else { None };
+             if let Some((current_size, current_modified)) = signature {
+                 if (current_size, current_modified) != (size, modified) {
+                     emit_copy_log(&handle, format!("{} changed since the folder was listed ({} -> {} bytes), copying the current version", file_name_display, size, current_size), LogLevel::Warn);
+                     total_filtered_bytes = (total_filtered_bytes + current_size).saturating_sub(size);
+                     size = current_size;
                  }
-             );
+             }
+
+             let name_lower = file_name_display.to_lowercase();
+             let options = CopyOptions {
+                 line_ending: line_ending.filter(|_| ends_with_any(&name_lower, &line_ending_extensions)),
+                 ..copy_options.clone()
+             };
+
+             // A same-volume rename is instant and atomic, so a cancel can't leave half a file behind.
+             // Converted files have to be rewritten, so they take the copy-then-delete route.
+             if move_files && options.line_ending.is_none() && std::fs::rename(&src, &dst).is_ok() {
+                 renamed_files += 1;
+                 copied_bytes_total += size;
+                 update_stats(copied_bytes_total, total_filtered_bytes);
+                 copied_files_list.push(file_name_display);
+                 copied_paths.push(dst.clone());
+                 continue;
+             }
+
+             // Copy with chunking
+             let mut on_progress = |delta: u64| {
+                 copied_bytes_total += delta;
+                 throttle.consume(delta, &should_cancel_clone, &is_paused_clone);
+                 update_stats(copied_bytes_total, total_filtered_bytes);
+             };
+             let copy_res = match per_file_timeout {
+                 Some(timeout) => copy_file_with_watchdog(&src, &dst, &options, &should_cancel_clone, &is_paused_clone, timeout, &mut on_progress),
+                 None => copy_file_chunked(&src, &dst, &options, &should_cancel_clone, &is_paused_clone, &mut on_progress),
+             };
              
              match copy_res {
-                 Ok(_) => {
+                 Ok(outcome) => {
+                     // A copy taken while the source changed is inconsistent; drop it so the next scan fetches it again
+                     if signature.is_some() && file_signature(&src) != signature {
+                         drifted_files += 1;
+                         let _ = std::fs::remove_file(&dst);
+                         emit_copy_log(&handle, format!("{} changed while it was being copied; discarded the copy, the next scan will retry it", file_name_display), LogLevel::Warn);
+                         continue;
+                     }
+                     if outcome.readonly_cleared {
+                         emit_copy_log(&handle, format!("Cleared read-only attribute to overwrite {}", dst.display()), LogLevel::Warn);
+                     }
+                     if let Some(e) = outcome.ownership_error {
+                         if !OWNERSHIP_WARNED.swap(true, Ordering::SeqCst) {
+                             emit_copy_log(&handle, format!("Could not preserve file ownership ({}); copies keep the current user's ownership", e), LogLevel::Warn);
+                         }
+                     }
+                     if outcome.reflinked {
+                         reflinked_files += 1;
+                         copied_bytes_total += size;
+                         update_stats(copied_bytes_total, total_filtered_bytes);
+                     }
+                     // Across volumes: the source goes only once its copy is complete
+                     if move_files {
+                         if let Err(e) = std::fs::remove_file(&src) {
+                             emit_copy_log(&handle, format!("Copied {} but could not remove the source: {}", file_name_display, e), LogLevel::Warn);
+                         }
+                     }
                      copied_files_list.push(file_name_display);
+                     copied_paths.push(dst.clone());
                  },
                  Err(e) => {
                      if e.contains("Cancelled") {
                          // Save partial
                          if !copied_files_list.is_empty() {
                              add_history_entry(&handle, HistoryEntry {
-                                 id: uuid::Uuid::new_v4().to_string(),
+                                 id: history_id.clone(),
                                  timestamp: Local::now().to_rfc3339(),
-                                 action_type: "COPY_CANCELLED".to_string(),
-                                 description: format!("Cancelled copying {}", folder_name_clone),
+                                 action_type: format!("{}_CANCELLED", action),
+                                 description: format!("Cancelled {} {}", doing, folder_name_clone),
                                  folder_name: format!("{} (Cancelled)", folder_name_clone),
                                  source_path: source_path_clone.to_string_lossy().to_string(),
                                  target_path: target_full_path_clone.to_string_lossy().to_string(),
                                  copied_files_count: copied_files_list.len(),
                                  total_size: copied_bytes_total,
                                  files: copied_files_list,
+                                 run_id: run_id.clone(),
+                                 command_results: vec![],
                              });
                          }
-                         return Err(fs_extra::error::Error::new(fs_extra::error::ErrorKind::Interrupted, "Cancelled by user"));
+                         crate::stats::record(&handle, |s| s.total_bytes_copied += copied_bytes_total);
+                         return Err(SyncError::Cancelled);
                      } else {
-                         emit_log(&handle, format!("Failed to copy {}: {}", file_name_display, e), "error");
+                         emit_copy_log(&handle, format!("Failed to copy {}: {}", file_name_display, e), LogLevel::Error);
+                         if e.starts_with(COPY_TIMED_OUT) {
+                             file_errors.error(format!("Failed to copy {}: {}", src.display(), e));
+                         }
                      }
                  }
              }
         }
 
+        if drifted_files > 0 {
+            emit_copy_log(&handle, format!("{} file(s) in {} changed during the copy and were skipped", drifted_files, folder_name_clone), LogLevel::Warn);
+        }
+        if move_files {
+            emit_copy_log(&handle, format!("Moved {} of {} files in {} by rename", renamed_files, copied_files_list.len(), folder_name_clone), LogLevel::Info);
+        }
+        if reflinked_files > 0 {
+            emit_copy_log(&handle, format!("Used reflink (copy-on-write) for {} of {} files in {}", reflinked_files, copied_files_list.len(), folder_name_clone), LogLevel::Info);
+        }
+
         // Done
+         crate::stats::record(&handle, |s| s.total_bytes_copied += copied_bytes_total);
          add_history_entry(&handle, HistoryEntry {
-             id: uuid::Uuid::new_v4().to_string(),
+             id: history_id.clone(),
              timestamp: Local::now().to_rfc3339(),
-             action_type: "COPY_COMPLETED".to_string(),
-             description: format!("Successfully copied {}", folder_name_clone),
+             action_type: format!("{}_COMPLETED", action),
+             description: format!("Successfully {} {}", done, folder_name_clone),
              folder_name: folder_name_clone.clone(),
              source_path: source_path_clone.to_string_lossy().to_string(),
              target_path: target_full_path_clone.to_string_lossy().to_string(),
              copied_files_count: copied_files_list.len(),
              total_size: copied_bytes_total,
              files: copied_files_list.clone(),
+             run_id: run_id.clone(),
+             command_results: vec![],
          });
+
+         if !config_clone.post_copy_commands.is_empty() {
+             run_post_copy_commands(&handle, &config_clone, &target_full_path_clone, &should_cancel_clone, &file_errors)?;
+         }
          
          // Deploy
          if config_clone.deploy_enabled {
-              if let Err(e) = deploy_to_remote(
+              settle_before_deploy(&handle, &config_clone, &copied_paths, &should_cancel_clone)?;
+              let deploy_started = Instant::now();
+              let deployed = deploy_to_remote(
                   &handle, 
                   &config_clone, 
                   &target_full_path_clone, 
                   &folder_name_clone,
                   should_cancel_clone,
                   is_paused_clone
-              ) {
-                  emit_log(&handle, format!("Deployment failed: {}", e), "error");
+              );
+              deploy_ms_clone.store(deploy_started.elapsed().as_millis() as u64, Ordering::SeqCst);
+              if let Err(e) = deployed {
+                  return Err(SyncError::Deploy(e));
               }
          }
         
         Ok(copied_bytes_total)
     });
 
-    match copy_task.await {
+    let outcome = copy_task.await;
+    let deploy_ms = deploy_ms.load(Ordering::SeqCst);
+    report.phases(|p| {
+        p.deploy_ms += deploy_ms;
+        p.copy_ms += (copy_started.elapsed().as_millis() as u64).saturating_sub(deploy_ms);
+    });
+
+    match outcome {
         Ok(Ok(_)) => {
             let success_msg = format!("Successfully copied: {}", folder_name);
-            emit_log(app_handle, success_msg.clone(), "success");
-            result.copied_folders.push(folder_name);
+            emit_copy_log(app_handle, success_msg.clone(), LogLevel::Success);
+            report.copied(folder_name);
+        },
+        Ok(Err(SyncError::Cancelled)) => {
+            let msg = format!("Copy cancelled: {}", folder_name);
+            emit_copy_log(app_handle, msg.clone(), LogLevel::Warn);
+        },
+        Ok(Err(SyncError::Deploy(e))) => {
+            // Files are in place locally, only the deploy step failed
+            emit_copy_log(app_handle, format!("Successfully copied: {}", folder_name), LogLevel::Success);
+            let err_msg = format!("Deployment of {} failed: {}", folder_name, e);
+            emit_copy_log(app_handle, err_msg.clone(), LogLevel::Error);
+            report.copied(folder_name.clone());
+            report.error(err_msg);
+            report.failed(folder_name);
         },
         Ok(Err(e)) => {
-            if let fs_extra::error::ErrorKind::Interrupted = e.kind {
-                let msg = format!("Copy cancelled: {}", folder_name);
-                emit_log(app_handle, msg.clone(), "warn");
-            } else {
-                let err_msg = format!("Failed to copy {}: {}", folder_name, e);
-                emit_log(app_handle, err_msg.clone(), "error");
-                result.errors.push(err_msg);
-            }
+            let err_msg = format!("Failed to copy {}: {}", folder_name, e);
+            emit_copy_log(app_handle, err_msg.clone(), LogLevel::Error);
+            report.error(err_msg);
+            report.failed(folder_name);
         },
         Err(e) => {
             let err_msg = format!("Copy task panic: {}", e);
-            emit_log(app_handle, err_msg.clone(), "error");
-            result.errors.push(err_msg);
+            emit_copy_log(app_handle, err_msg.clone(), LogLevel::Error);
+            report.error(err_msg);
+            report.failed(folder_name);
+        }
+    }
+}
+
+/// Payload of `scan-complete`: the whole result plus the counts a summary toast needs.
+#[derive(Debug, serde::Serialize, Clone)]
+struct ScanCompleteEvent<'a> {
+    #[serde(flatten)]
+    result: &'a ScanResult,
+    skipped_folders: usize, // Found but neither copied nor failed, e.g. cancelled or not complete yet
+    failed_count: usize,
+    clean: bool, // No errors at all
+}
+
+/// Announces the finished scan to the UI and records its phase timings in history.
+pub fn report_scan_summary<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, result: &ScanResult) {
+    let skipped_folders = result.found_folders.iter()
+        .filter(|f| !result.copied_folders.contains(f) && !result.failed_folders.contains(f))
+        .count();
+    let _ = app_handle.emit("scan-complete", ScanCompleteEvent {
+        result,
+        skipped_folders,
+        failed_count: result.failed_folders.len(),
+        clean: result.errors.is_empty(),
+    });
+    if !result.copied_folders.is_empty() {
+        record_successful_scan(app_handle);
+    }
+
+    let phases = &result.phase_durations;
+    add_history_entry(app_handle, HistoryEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        timestamp: Local::now().to_rfc3339(),
+        action_type: "SCAN_SUMMARY".to_string(),
+        description: format!(
+            "Found {}, copied {}, errors {} | enumeration {}ms, filtering {}ms, copy {}ms, deploy {}ms",
+            result.found_folders.len(),
+            result.copied_folders.len(),
+            result.errors.len(),
+            phases.enumeration_ms,
+            phases.filtering_ms,
+            phases.copy_ms,
+            phases.deploy_ms
+        ),
+        folder_name: "".to_string(),
+        source_path: "".to_string(),
+        target_path: "".to_string(),
+        copied_files_count: 0,
+        total_size: 0,
+        files: vec![],
+        run_id: result.run_id.clone(),
+        command_results: vec![],
+    });
+}
+
+const STAGING_SUFFIXES: [&str; 2] = [".partial", ".incoming"];
+// Written into every staging dir the tool creates. A folder from the share whose name merely
+// ends in one of the suffixes has no marker and is never removed.
+const STAGING_MARKER: &str = ".file-sync-staging";
+
+// Removes `*.partial` / `*.incoming` dirs left by interrupted copies once they are older than
+// staging_max_age_hours. Only dirs carrying STAGING_MARKER are touched, so user data is never removed.
+pub fn cleanup_staging<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, config: &AppConfig) -> Vec<String> {
+    let max_age = std::time::Duration::from_secs(config.staging_max_age_hours * 3600);
+    let mut roots: Vec<PathBuf> = vec![PathBuf::from(&config.local_path)];
+    roots.extend(config.additional_local_paths.iter().map(PathBuf::from));
+    for task in &config.tasks {
+        if let Some(local) = &task.local_path {
+            roots.push(PathBuf::from(local));
+        }
+    }
+    roots.sort();
+    roots.dedup();
+
+    let mut removed = Vec::new();
+    for root in roots {
+        // Staging dirs sit next to their final folder: <local>/<name> or <local>/<date>/<name>
+        let mut dirs_to_visit = vec![(root, 0)];
+        while let Some((dir, depth)) = dirs_to_visit.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+            for entry in entries.flatten() {
+                if !entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                    continue;
+                }
+                let path = entry.path();
+                let name = entry.file_name().to_string_lossy().to_string();
+                if !STAGING_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) || !path.join(STAGING_MARKER).is_file() {
+                    if depth < 1 {
+                        dirs_to_visit.push((path, depth + 1));
+                    }
+                    continue;
+                }
+                let age = entry.metadata()
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|t| t.elapsed().ok())
+                    .unwrap_or_default();
+                if age < max_age {
+                    continue;
+                }
+                match std::fs::remove_dir_all(&path) {
+                    Ok(_) => {
+                        emit_system_log(app_handle, format!("Removed orphaned staging folder {:?}", path), LogLevel::Info);
+                        removed.push(path.to_string_lossy().to_string());
+                    },
+                    Err(e) => {
+                        emit_system_log(app_handle, format!("Failed to remove staging folder {:?}: {}", path, e), LogLevel::Warn);
+                    }
+                }
+            }
         }
     }
+    removed
 }
 
 pub async fn scan_and_copy<R: tauri::Runtime>(
@@ -453,54 +1936,70 @@ pub async fn scan_and_copy<R: tauri::Runtime>(
     should_cancel: Arc<AtomicBool>,
     is_paused: Arc<AtomicBool>
 ) -> ScanResult {
-    let mut result = ScanResult {
-        scanned_paths: 0,
-        found_folders: vec![],
-        copied_folders: vec![],
-        errors: vec![],
-    };
+    let report = ScanReport::new();
+    let _run_scope = RunScope::enter(app_handle, &report.run_id());
+
+    if config.safe_mode {
+        emit_log(app_handle, "SAFE MODE: only reading and copying locally; deploy, remote commands and deletions are disabled".to_string(), LogLevel::Warn);
+    }
 
-    let re_version = Regex::new(r"^(\d{4}_\d{2}_\d{2}_\d{2}_\d{2})\((.+)\)$").unwrap();
+    let folder_patterns = compile_folder_patterns(&config.folder_patterns);
+    for err in &folder_patterns.errors {
+        emit_log(app_handle, err.clone(), LogLevel::Error);
+        report.error(err.clone());
+    }
     let now_local = Local::now();
     let now = now_local.naive_local();
     let today = now.date();
-    let yesterday = today - Duration::days(1);
+    let earliest = today - Duration::days(i64::from(config.max_age_days));
     
     // Check Time Ranges
     if !config.time_ranges.is_empty() {
         let current_time = now_local.time();
         let mut in_range = false;
         for range in &config.time_ranges {
-            let parts: Vec<&str> = range.split('-').collect();
-            if parts.len() == 2 {
-                if let (Ok(start), Ok(end)) = (
-                    NaiveTime::parse_from_str(parts[0], "%H:%M"),
-                    NaiveTime::parse_from_str(parts[1], "%H:%M")
-                ) {
-                    if current_time >= start && current_time <= end {
-                        in_range = true;
-                        break;
-                    }
+            if let Some((start, end)) = crate::config::parse_time_range(range) {
+                if current_time >= start && current_time <= end {
+                    in_range = true;
+                    break;
                 }
             }
         }
         
         if !in_range {
-             emit_log(app_handle, format!("Current time {} is outside of configured time ranges {:?}. Skipping scan.", current_time.format("%H:%M"), config.time_ranges), "info");
-             return result;
+             emit_log(app_handle, format!("Current time {} is outside of configured time ranges {:?}. Skipping scan.", current_time.format("%H:%M"), config.time_ranges), LogLevel::Info);
+             return report.finish();
         }
     }
 
+    // Another copy of the app (portable + installed) may be scanning into the same destination
+    let _scan_locks = match acquire_scan_locks(config) {
+        Ok(locks) => locks,
+        Err(e) => {
+            emit_log(app_handle, format!("{}. Skipping scan.", e), LogLevel::Error);
+            report.error(e);
+            return report.finish();
+        }
+    };
+
+    // Each source folder is copied at most once per scan, even if several tasks match it
+    let mut copied_sources: HashSet<PathBuf> = HashSet::new();
+    let mut jobs: Vec<CopyJob> = vec![];
+
+    let enumeration_started = Instant::now();
+    let listings = list_version_paths(app_handle, config, &folder_patterns.regexes, &should_cancel).await;
+    report.phases(|p| p.enumeration_ms += enumeration_started.elapsed().as_millis() as u64);
+
     for task in &config.tasks {
         if !task.enabled { continue; }
         
         if should_cancel.load(Ordering::SeqCst) {
-            emit_log(app_handle, "Scan cancelled by user".to_string(), "info");
-            return result;
+            emit_log(app_handle, "Scan cancelled by user".to_string(), LogLevel::Info);
+            return report.finish();
         }
 
-        result.scanned_paths += 1;
-        emit_log(app_handle, format!("Task [{}]: Scanning {}", task.name, task.remote_path), "info");
+        report.update(|r| r.scanned_paths += 1);
+        emit_log(app_handle, format!("Task [{}]: Scanning {}", task.name, task.remote_path), LogLevel::Info);
         
         let path = Path::new(&task.remote_path);
         let local_parent = if let Some(custom_local) = &task.local_path {
@@ -509,54 +2008,39 @@ pub async fn scan_and_copy<R: tauri::Runtime>(
             Path::new(&config.local_path)
         };
 
+        if let Err(err_msg) = prepare_local_path(local_parent, config.create_local_path_if_missing) {
+            emit_log(app_handle, format!("Task [{}]: {}", task.name, err_msg), LogLevel::Error);
+            report.error(err_msg);
+            continue;
+        }
+
         match &task.rule {
             MatchRule::VersionMatch(target_version) => {
-                 let mut entries = match fs::read_dir(path).await {
-                    Ok(entries) => entries,
-                    Err(e) => {
-                        let err_msg = format!("Failed to read {}: {}", task.remote_path, e);
-                        emit_log(app_handle, err_msg.clone(), "error");
-                        result.errors.push(err_msg);
+                // Collect candidates
+                let mut candidates: Vec<Candidate> = match listings.get(&task.remote_path) {
+                    Some(Listing::Candidates(candidates)) => candidates.clone(),
+                    Some(Listing::Failed(err_msg)) => {
+                        emit_log(app_handle, err_msg.clone(), LogLevel::Error);
+                        report.error(err_msg.clone());
+                        continue;
+                    }
+                    Some(Listing::Cancelled) => {
+                        emit_log(app_handle, "Scan cancelled by user".to_string(), LogLevel::Info);
+                        return report.finish();
+                    }
+                    None => {
+                        let err_msg = format!("Failed to read {}: listing did not complete", task.remote_path);
+                        emit_log(app_handle, err_msg.clone(), LogLevel::Error);
+                        report.error(err_msg);
                         continue;
                     }
                 };
-
-                // Collect candidates
-                let mut candidates: Vec<Candidate> = Vec::new();
                 let mut tree_view: Vec<String> = Vec::new();
 
-                while let Ok(Some(entry)) = entries.next_entry().await {
-                    if should_cancel.load(Ordering::SeqCst) {
-                        emit_log(app_handle, "Scan cancelled by user".to_string(), "info");
-                        return result;
-                    }
-                    
-                    let file_name = entry.file_name();
-                    let name_str = file_name.to_string_lossy().to_string();
-                    
-                    let mut dt = NaiveDateTime::MIN;
-                    if let Some(caps) = re_version.captures(&name_str) {
-                         if let Some(date_part) = caps.get(1) {
-                             if let Ok(parsed) = NaiveDateTime::parse_from_str(date_part.as_str(), "%Y_%m_%d_%H_%M") {
-                                 dt = parsed;
-                             }
-                         }
-                    }
-                    
-                    candidates.push(Candidate {
-                        path: entry.path(),
-                        name: name_str.clone(),
-                        version: if let Some(caps) = re_version.captures(&name_str) {
-                            caps.get(2).map(|m| m.as_str().to_string()).unwrap_or_default()
-                        } else {
-                            String::new()
-                        },
-                        datetime: dt,
-                    });
-                }
-                
+                let filtering_started = Instant::now();
+
                 // Sort
-                candidates.sort_by(|a, b| b.datetime.cmp(&a.datetime));
+                candidates.sort_by(|a, b| compare_candidates(a, b, &config.tie_breaker));
                 
                 // Tree view
                 for cand in candidates.iter().take(20) {
@@ -566,107 +2050,282 @@ pub async fn scan_and_copy<R: tauri::Runtime>(
                      tree_view.push(format!("└─ ... ({} more files)", candidates.len() - 20));
                 }
                 if !tree_view.is_empty() {
-                     emit_log(app_handle, format!("Directory structure (partial):\n{}", tree_view.join("\n")), "info");
+                     emit_log(app_handle, format!("Directory structure (partial):\n{}", tree_view.join("\n")), LogLevel::Info);
                 }
                 
-                // Filter by version
-                let mut version_matches: Vec<&Candidate> = candidates.iter()
-                    .filter(|c| c.version == *target_version)
+                // Pinned folders are copied whatever their date or version; file filters still apply
+                let pinned: Vec<&Candidate> = candidates.iter()
+                    .filter(|c| config.pinned_folders.contains(&c.name))
                     .collect();
+
+                if target_version.is_empty() {
+                    emit_log(app_handle, format!("Task [{}] has no target version, empty_versions_behavior = {}", task.name, config.empty_versions_behavior), LogLevel::Info);
+                }
+
+                let version_matches = select_version_matches(config, task, target_version, &candidates);
+                report.phases(|p| p.filtering_ms += filtering_started.elapsed().as_millis() as u64);
                 
+                // The newest `versions_to_keep` builds, so older ones stay staged for a rollback
+                let mut copied_versions: Vec<&String> = vec![];
                 if version_matches.is_empty() {
-                    emit_log(app_handle, format!("No candidates found for version {}", target_version), "info");
-                    continue;
+                    emit_log(app_handle, format!("No candidates found for version {}", target_version), LogLevel::Info);
                 }
-                
-                version_matches.sort_by(|a, b| b.datetime.cmp(&a.datetime));
-                
-                if let Some(latest) = version_matches.first() {
-                    let folder_date = latest.datetime.date();
-                    emit_log(app_handle, format!("Latest candidate for {}: {} ({})", target_version, latest.name, folder_date), "info");
+                for (rank, candidate) in version_matches.iter().take(config.versions_to_keep.max(1)).enumerate() {
+                    let folder_date = candidate.datetime.date();
+                    if rank == 0 {
+                        emit_log(app_handle, format!("Latest candidate for {}: {} ({})", target_version, candidate.name, folder_date), LogLevel::Info);
+                    } else {
+                        emit_log(app_handle, format!("Candidate #{} for {}: {} ({})", rank + 1, target_version, candidate.name, folder_date), LogLevel::Info);
+                    }
 
-                    if folder_date == today || folder_date == yesterday {
-                        result.found_folders.push(latest.name.clone());
+                    // Dated in the future means clock skew or a typo, not a new build
+                    let in_window = folder_date >= earliest && folder_date <= today;
+                    if in_window && !copied_sources.insert(candidate.path.clone()) {
+                        emit_log(app_handle, format!("{} was already copied by an earlier task in this scan", candidate.name), LogLevel::Info);
+                        copied_versions.push(&candidate.name);
+                    } else if in_window {
+                        report.found(candidate.name.clone());
                         
-                        perform_copy(
-                            app_handle,
-                            latest.path.clone(),
-                            latest.name.clone(),
-                            local_parent,
-                            config,
-                            should_cancel.clone(),
-                            is_paused.clone(),
-                            &mut result
-                        ).await;
+                        jobs.push(CopyJob {
+                            source_path: candidate.path.clone(),
+                            folder_name: candidate.name.clone(),
+                            target_parent: local_parent.to_path_buf(),
+                        });
+                        copied_versions.push(&candidate.name);
                         
                     } else {
-                        emit_log(app_handle, format!("Ignored {} because date {} is not Today ({}) or Yesterday ({})", latest.name, folder_date, today, yesterday), "info");
+                        emit_log(app_handle, format!("Ignored {} because date {} is outside the scan window {} to {} (max_age_days = {})", candidate.name, folder_date, earliest, today, config.max_age_days), LogLevel::Info);
                     }
                 }
+
+                for cand in pinned {
+                    if copied_versions.contains(&&cand.name) || !copied_sources.insert(cand.path.clone()) {
+                        continue;
+                    }
+                    emit_log(app_handle, format!("Copying pinned folder {}", cand.name), LogLevel::Info);
+                    report.found(cand.name.clone());
+                    jobs.push(CopyJob {
+                        source_path: cand.path.clone(),
+                        folder_name: cand.name.clone(),
+                        target_parent: local_parent.to_path_buf(),
+                    });
+                }
             },
             MatchRule::DateMatch(format_str) => {
                 let fmt = if format_str.is_empty() { "%y%m%d" } else { format_str };
-                let target_name = now_local.format(fmt).to_string();
-                
-                emit_log(app_handle, format!("Checking for date-based folder: {}", target_name), "info");
+                let today_name = now_local.format(fmt).to_string();
                 
-                let target_path = path.join(&target_name);
+                emit_log(app_handle, format!("Checking for date-based folder: {}", today_name), LogLevel::Info);
+
+                // Today's folder plus any pinned date folders, which are copied regardless of date
+                let mut date_folders = vec![today_name.clone()];
+                for pinned in &config.pinned_folders {
+                    if *pinned != today_name && path.join(pinned).is_dir() {
+                        emit_log(app_handle, format!("Copying pinned folder {}", pinned), LogLevel::Info);
+                        date_folders.push(pinned.clone());
+                    }
+                }
                 
-                // Check if exists
-                if target_path.exists() && target_path.is_dir() {
-                    emit_log(app_handle, format!("Found candidate folder: {}", target_name), "success");
-                    
-                    // Instead of treating the folder itself as the unit to copy/skip,
-                    // we now treat it as a container that may hold multiple build directories.
-                    // We need to list its contents and copy them individually if they don't exist locally.
+                for target_name in date_folders {
+                    let target_path = path.join(&target_name);
                     
-                    let local_target_base = local_parent.join(&target_name);
-                    
-                    // Scan subdirectories in the remote folder
-                    let mut sub_entries = match fs::read_dir(&target_path).await {
-                        Ok(e) => e,
-                        Err(e) => {
-                            let err = format!("Failed to list contents of {}: {}", target_path.display(), e);
-                            emit_log(app_handle, err.clone(), "error");
-                            result.errors.push(err);
-                            continue;
+                    // Check if exists
+                    if target_path.exists() && target_path.is_dir() {
+                        emit_log(app_handle, format!("Found candidate folder: {}", target_name), LogLevel::Success);
+                        
+                        // Instead of treating the folder itself as the unit to copy/skip,
+                        // we now treat it as a container that may hold multiple build directories.
+                        // We need to list its contents and copy them individually if they don't exist locally.
+                        
+                        let local_target_base = local_parent.join(&target_name);
+                        
+                        // Scan subdirectories in the remote folder
+                        let enumeration_started = Instant::now();
+                        let mut sub_entries = match fs::read_dir(&target_path).await {
+                            Ok(e) => e,
+                            Err(e) => {
+                                let err = format!("Failed to list contents of {}: {}", target_path.display(), e);
+                                emit_log(app_handle, err.clone(), LogLevel::Error);
+                                report.error(err);
+                                continue;
+                            }
+                        };
+
+                        // List everything first so listing time isn't mixed with copy time
+                        let mut sub_dirs = Vec::new();
+                        while let Ok(Some(entry)) = sub_entries.next_entry().await {
+                             let sub_path = entry.path();
+                             if sub_path.is_dir() {
+                                 sub_dirs.push((sub_path, entry.file_name().to_string_lossy().to_string()));
+                             }
                         }
-                    };
+                        report.phases(|p| p.enumeration_ms += enumeration_started.elapsed().as_millis() as u64);
 
-                    let mut found_any_new = false;
-                    
-                    while let Ok(Some(entry)) = sub_entries.next_entry().await {
-                         let sub_path = entry.path();
-                         if sub_path.is_dir() {
-                             let sub_name = entry.file_name().to_string_lossy().to_string();
-                             let local_sub_path = local_target_base.join(&sub_name);
-                             
+                        let found_any_new = !sub_dirs.is_empty();
+                        
+                        for (sub_path, sub_name) in sub_dirs {
                              // Always scan subdirectories to support incremental updates
-                             found_any_new = true;
-                             result.found_folders.push(format!("{}/{}", target_name, sub_name));
+                             report.found(format!("{}/{}", target_name, sub_name));
                              
-                             perform_copy(
-                                 app_handle,
-                                 sub_path,
-                                 sub_name, // Copy as sub_name
-                                 &local_target_base, // Into local/Date/
-                                 config,
-                                 should_cancel.clone(),
-                                 is_paused.clone(),
-                                 &mut result
-                             ).await;
-                         }
-                    }
-                    
-                    if !found_any_new {
-                        emit_log(app_handle, format!("No new build directories found in {}", target_name), "info");
-                    }
+                             jobs.push(CopyJob {
+                                 source_path: sub_path,
+                                 folder_name: sub_name,
+                                 target_parent: local_target_base.clone(),
+                             });
+                        }
+                        
+                        if !found_any_new {
+                            emit_log(app_handle, format!("No new build directories found in {}", target_name), LogLevel::Info);
+                        }
 
-                } else {
-                    emit_log(app_handle, format!("Folder {} does not exist in {}", target_name, task.remote_path), "info");
+                    } else {
+                        emit_log(app_handle, format!("Folder {} does not exist in {}", target_name, task.remote_path), LogLevel::Info);
+                    }
                 }
             }
         }
     }
+
+    // Everything to copy is known now, so the whole run's size can be shown before the first byte moves
+    // Each folder also goes to every additional_local_paths entry
+    let jobs: Vec<(CopyJob, Vec<CopyJob>)> = jobs.into_iter()
+        .map(|job| { let mirrors = mirror_jobs(&job, config); (job, mirrors) })
+        .collect();
+    let estimate_targets: Vec<(PathBuf, PathBuf)> = jobs.iter()
+        .flat_map(|(job, mirrors)| std::iter::once(job).chain(mirrors))
+        .map(|job| (job.source_path.clone(), job.target_parent.join(&job.folder_name)))
+        .collect();
+    if let Ok(estimates) = tauri::async_runtime::spawn_blocking(move || {
+        estimate_targets.into_iter()
+            .map(|(source, target)| { let bytes = pending_bytes(&source, &target); (target, bytes) })
+            .collect::<Vec<_>>()
+    }).await {
+        for (target, bytes) in estimates {
+            report.overall.estimate(target, bytes);
+        }
+    }
+    report.overall.emit(app_handle);
+
+    // Mirrors only copy: they go first so a move still has its source, and deploy runs once, from local_path
+    let mirror_config = AppConfig { deploy_enabled: false, move_instead_of_copy: false, ..config.clone() };
+    for (job, mirrors) in jobs {
+        for mirror in mirrors {
+            if should_cancel.load(Ordering::SeqCst) {
+                break;
+            }
+            let mirror_report = report.mirror();
+            perform_copy(
+                app_handle,
+                mirror.source_path,
+                mirror.folder_name,
+                &mirror.target_parent,
+                &mirror_config,
+                should_cancel.clone(),
+                is_paused.clone(),
+                &mirror_report
+            ).await;
+            report.absorb_mirror(&mirror_report);
+        }
+        if should_cancel.load(Ordering::SeqCst) {
+            emit_log(app_handle, "Scan cancelled by user".to_string(), LogLevel::Info);
+            return report.finish();
+        }
+        perform_copy(
+            app_handle,
+            job.source_path,
+            job.folder_name,
+            &job.target_parent,
+            config,
+            should_cancel.clone(),
+            is_paused.clone(),
+            &report
+        ).await;
+    }
+
+    // Errors go straight to the UI, so they follow the same redaction as log events
+    let mut result = report.finish();
+    if config.redact_paths {
+        result.errors = result.errors.iter().map(|e| redact_paths(e)).collect();
+    }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version_task(id: &str, version: &str) -> ScanTask {
+        ScanTask {
+            id: id.to_string(),
+            enabled: true,
+            name: id.to_string(),
+            remote_path: "/builds".to_string(),
+            local_path: Some(format!("/local/{}", id)),
+            rule: MatchRule::VersionMatch(version.to_string()),
+        }
+    }
+
+    fn candidate(config: &AppConfig, name: &str) -> Candidate {
+        let patterns = compile_folder_patterns(&config.folder_patterns);
+        let parsed = parse_folder_name(&patterns.regexes, &config.datetime_format, name, &config.default_version)
+            .expect("name fits the default folder pattern");
+        Candidate {
+            path: Path::new("/builds").join(name),
+            name: name.to_string(),
+            version: parsed.version,
+            datetime: parsed.datetime,
+            mtime: None,
+        }
+    }
+
+    #[test]
+    fn overlapping_versions_are_copied_once_by_their_own_task() {
+        let config = AppConfig {
+            empty_versions_behavior: "newest_any".to_string(),
+            tasks: vec![version_task("ums", "UMS"), version_task("ums2", "UMS2"), version_task("any", "")],
+            ..AppConfig::default()
+        };
+        let candidates = vec![
+            candidate(&config, "2026_10_15_09_00(UMS2)"),
+            candidate(&config, "2026_10_15_08_00(UMS)"),
+        ];
+
+        let mut picked_by: HashMap<&str, Vec<&str>> = HashMap::new();
+        for task in &config.tasks {
+            let MatchRule::VersionMatch(version) = &task.rule else { unreachable!() };
+            for picked in select_version_matches(&config, task, version, &candidates) {
+                picked_by.entry(picked.name.as_str()).or_default().push(task.id.as_str());
+            }
+        }
+
+        assert_eq!(picked_by["2026_10_15_09_00(UMS2)"], ["ums2"]);
+        assert_eq!(picked_by["2026_10_15_08_00(UMS)"], ["ums"]);
+    }
+
+    fn newest<'a>(candidates: &'a [Candidate], tie_breaker: &str) -> &'a str {
+        let mut sorted: Vec<&Candidate> = candidates.iter().collect();
+        sorted.sort_by(|a, b| compare_candidates(a, b, tie_breaker));
+        &sorted[0].name
+    }
+
+    #[test]
+    fn equal_datetimes_pick_the_same_candidate_in_any_order() {
+        let config = AppConfig::default();
+        let older = SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000);
+        let newer = older + std::time::Duration::from_secs(60);
+        let a = Candidate { mtime: Some(newer), ..candidate(&config, "2026_10_15_09_00(1.3.9)") };
+        let b = Candidate { mtime: Some(older), ..candidate(&config, "2026_10_15_09_00(1.3.9.P02)") };
+        assert_eq!(a.datetime, b.datetime);
+
+        for tie_breaker in ["mtime", "name"] {
+            let forward = newest(&[a.clone(), b.clone()], tie_breaker).to_string();
+            let reverse = newest(&[b.clone(), a.clone()], tie_breaker).to_string();
+            assert_eq!(forward, reverse, "tie_breaker = {}", tie_breaker);
+        }
+        // mtime first, then the name; "name" ignores mtime
+        assert_eq!(newest(&[b.clone(), a.clone()], "mtime"), a.name);
+        assert_eq!(newest(&[a.clone(), b.clone()], "name"), b.name);
+        let same_mtime = Candidate { mtime: Some(newer), ..b.clone() };
+        assert_eq!(newest(&[a.clone(), same_mtime], "mtime"), b.name);
+    }
+}
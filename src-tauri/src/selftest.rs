@@ -0,0 +1,159 @@
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::State;
+
+use crate::config::{AppConfig, MatchRule, ScanTask};
+use crate::{deploy, scanner, AppState};
+
+const SELF_TEST_VERSION: &str = "selftest";
+
+#[derive(Debug, serde::Serialize, Clone)]
+pub struct SelfTestStage {
+    pub name: String, // "prepare", "scan", "copy", "deploy", "cleanup"
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl SelfTestStage {
+    fn new(name: &str, ok: bool, detail: String) -> Self {
+        Self { name: name.to_string(), ok, detail }
+    }
+}
+
+/// Runs the real scan/copy (and deploy, if a server is enabled) against throwaway folders
+/// using the user's filters, then removes everything it created.
+#[tauri::command]
+pub async fn self_test(app_handle: tauri::AppHandle, state: State<'_, AppState>) -> Result<Vec<SelfTestStage>, String> {
+    if state.is_scanning.swap(true, Ordering::SeqCst) {
+        return Err("Operation already in progress".to_string());
+    }
+    state.should_cancel.store(false, Ordering::SeqCst);
+    state.is_paused.store(false, Ordering::SeqCst);
+    let config = state.effective_config();
+    state.is_self_testing.store(true, Ordering::SeqCst);
+    let stages = run_self_test(&app_handle, &config, state.should_cancel.clone(), state.is_paused.clone()).await;
+    state.is_self_testing.store(false, Ordering::SeqCst);
+    state.is_scanning.store(false, Ordering::SeqCst);
+    Ok(stages)
+}
+
+async fn run_self_test(
+    app_handle: &tauri::AppHandle,
+    config: &AppConfig,
+    should_cancel: Arc<AtomicBool>,
+    is_paused: Arc<AtomicBool>
+) -> Vec<SelfTestStage> {
+    let mut stages = vec![];
+    let sandbox = std::env::temp_dir().join(format!("file-sync-self-test-{}", uuid::Uuid::new_v4()));
+    let source_root = sandbox.join("source");
+    let local_root = sandbox.join("local");
+
+    // A folder named like a real build, holding one file that passes the configured filters
+    let folder_name = format!("{}({})", chrono::Local::now().format("%Y_%m_%d_%H_%M"), SELF_TEST_VERSION);
+    let file_name = dummy_file_name(config);
+    let prepared = fs::create_dir_all(source_root.join(&folder_name))
+        .and_then(|_| fs::create_dir_all(&local_root))
+        .and_then(|_| fs::write(source_root.join(&folder_name).join(&file_name), b"file sync self test\n"));
+    if let Err(e) = prepared {
+        stages.push(SelfTestStage::new("prepare", false, format!("Failed to create sandbox {}: {}", sandbox.display(), e)));
+        let _ = fs::remove_dir_all(&sandbox);
+        return stages;
+    }
+    stages.push(SelfTestStage::new("prepare", true, format!("Sandbox {}", sandbox.display())));
+
+    let test_config = sandbox_config(config, &source_root, &local_root);
+    let remote_dir = test_config.servers.first().map(|s| s.remote_path.clone());
+
+    let result = scanner::scan_and_copy(app_handle, &test_config, should_cancel, is_paused).await;
+
+    let found = result.found_folders.contains(&folder_name);
+    stages.push(SelfTestStage::new("scan", found, if found {
+        format!("Matched {}", folder_name)
+    } else {
+        format!("{} was not matched; errors: {:?}", folder_name, result.errors)
+    }));
+
+    let copied = local_root.join(&folder_name).join(&file_name).is_file();
+    stages.push(SelfTestStage::new("copy", copied, if copied {
+        format!("Copied {}", file_name)
+    } else {
+        format!("{} is missing in the destination; errors: {:?}", file_name, result.errors)
+    }));
+
+    let mut cleanup_errors = vec![];
+    match (test_config.servers.first(), remote_dir) {
+        (Some(server), Some(remote_dir)) => {
+            // Removing the test folder also tells us whether the upload really landed
+            let remote_folder = format!("{}/{}", remote_dir, folder_name);
+            let server = server.clone();
+            let handle = app_handle.clone();
+            let removed = tauri::async_runtime::spawn_blocking(move || {
+                let removed = deploy::remove_remote_dir(&handle, &server, &remote_folder);
+                let _ = deploy::remove_remote_dir(&handle, &server, &remote_dir);
+                removed
+            }).await.map_err(|e| e.to_string()).and_then(|r| r);
+            match removed {
+                Ok(true) => stages.push(SelfTestStage::new("deploy", true, "Uploaded to the first enabled server".to_string())),
+                Ok(false) => stages.push(SelfTestStage::new("deploy", false, format!("Upload did not arrive; errors: {:?}", result.errors))),
+                Err(e) => {
+                    stages.push(SelfTestStage::new("deploy", false, e.clone()));
+                    cleanup_errors.push(e);
+                }
+            }
+        },
+        _ => stages.push(SelfTestStage::new("deploy", true, "Skipped: deployment disabled or no enabled server".to_string())),
+    }
+
+    if let Err(e) = fs::remove_dir_all(&sandbox) {
+        cleanup_errors.push(format!("Failed to remove {}: {}", sandbox.display(), e));
+    }
+    stages.push(SelfTestStage::new("cleanup", cleanup_errors.is_empty(), cleanup_errors.join("; ")));
+    stages
+}
+
+fn dummy_file_name(config: &AppConfig) -> String {
+    let keyword = config.filename_includes.first().cloned().unwrap_or_default();
+    let extension = config.file_extensions.first()
+        .map(|e| e.trim_start_matches('.').to_string())
+        .unwrap_or_else(|| "txt".to_string());
+    format!("{}self-test.{}", keyword, extension)
+}
+
+// The user's config with every path pointed into the sandbox: no mirrors, hooks or prechecks.
+// Deploy keeps the first enabled server but uploads straight into /tmp (no staging) and runs no
+// commands, so production folders are never touched.
+fn sandbox_config(config: &AppConfig, source_root: &Path, local_root: &Path) -> AppConfig {
+    let mut test_config = config.clone();
+    test_config.tasks = vec![ScanTask {
+        id: "self-test".to_string(),
+        enabled: true,
+        name: "Self test".to_string(),
+        remote_path: source_root.to_string_lossy().to_string(),
+        local_path: None,
+        rule: MatchRule::VersionMatch(SELF_TEST_VERSION.to_string()),
+    }];
+    test_config.local_path = local_root.to_string_lossy().to_string();
+    test_config.time_ranges = vec![];
+    test_config.pinned_folders = vec![];
+    test_config.additional_local_paths = vec![];
+    test_config.webhook_url = None;
+    test_config.post_commands = vec![];
+    test_config.post_copy_commands = vec![];
+    test_config.remote_precheck_paths = vec![];
+    test_config.remote_precheck_remove = false;
+    test_config.backup_before_deploy = false;
+    test_config.post_copy_delay_ms = 0;
+
+    let server = config.servers.iter().find(|s| s.enabled).filter(|_| config.deploy_enabled).cloned();
+    test_config.deploy_enabled = server.is_some();
+    test_config.servers = server.into_iter().map(|mut s| {
+        s.remote_path = format!("/tmp/file-sync-self-test-{}", uuid::Uuid::new_v4());
+        s.staging_path = None;
+        s.remote_folder_name_template = String::new();
+        s.deploy_single_artifact = None;
+        s
+    }).collect();
+    test_config
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::AppState;
+
+/// Lifetime counters of this installation, kept in stats.json so they survive restarts.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StatsSnapshot {
+    pub total_scans: u64,
+    pub total_folders_copied: u64,
+    pub total_bytes_copied: u64,
+    pub total_deploys: u64,
+    pub total_errors: u64,
+    #[serde(default)]
+    pub since: String, // RFC 3339, when counting started or was last reset
+}
+
+pub struct RuntimeStats {
+    counters: Mutex<StatsSnapshot>,
+    path: PathBuf,
+}
+
+impl RuntimeStats {
+    pub fn load<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>) -> Self {
+        Self::load_from(crate::config::app_data_dir(app_handle).join("stats.json"))
+    }
+
+    pub fn load_from(path: PathBuf) -> Self {
+        let counters = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(fresh_snapshot);
+        Self { counters: Mutex::new(counters), path }
+    }
+
+    pub fn snapshot(&self) -> StatsSnapshot {
+        self.counters.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn update(&self, f: impl FnOnce(&mut StatsSnapshot)) {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut counters);
+        self.save(&counters);
+    }
+
+    pub fn reset(&self) -> StatsSnapshot {
+        let mut counters = self.counters.lock().unwrap_or_else(|e| e.into_inner());
+        *counters = fresh_snapshot();
+        self.save(&counters);
+        counters.clone()
+    }
+
+    // Best effort: losing one increment is better than failing the scan that caused it
+    fn save(&self, counters: &StatsSnapshot) {
+        if let Some(parent) = self.path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        if let Err(e) = fs::write(&self.path, serde_json::to_string_pretty(counters).unwrap_or_default()) {
+            log::warn!("Failed to save {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+fn fresh_snapshot() -> StatsSnapshot {
+    StatsSnapshot { since: chrono::Local::now().to_rfc3339(), ..Default::default() }
+}
+
+/// Updates the app-wide counters; a no-op when there is no app state (e.g. early startup)
+/// and during a self test.
+pub fn record<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, f: impl FnOnce(&mut StatsSnapshot)) {
+    if let Some(state) = app_handle.try_state::<AppState>() {
+        if !state.is_self_testing.load(std::sync::atomic::Ordering::SeqCst) {
+            state.stats.update(f);
+        }
+    }
+}
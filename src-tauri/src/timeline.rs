@@ -0,0 +1,61 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tauri::Manager;
+
+// ~10 minutes of samples at the scanner's 500ms emit interval
+const MAX_SAMPLES: usize = 1200;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SpeedSample {
+    pub elapsed_ms: u64,
+    pub copied_bytes: u64,
+    pub total_bytes: u64,
+    pub speed: u64, // bytes per second since the previous sample
+}
+
+/// Speed samples of the most recent copy/deploy per folder, for throughput graphs.
+#[derive(Default)]
+pub struct TransferTimelines {
+    folders: Mutex<HashMap<String, VecDeque<SpeedSample>>>,
+}
+
+pub fn record_sample<R: tauri::Runtime>(app_handle: &tauri::AppHandle<R>, folder: &str, elapsed_ms: u64, copied: u64, total: u64) {
+    let Some(state) = app_handle.try_state::<TransferTimelines>() else {
+        return;
+    };
+    let mut folders = state.folders.lock().unwrap();
+    let samples = folders.entry(folder.to_string()).or_default();
+
+    // Elapsed going backwards means a new transfer of the same folder started
+    if samples.back().is_some_and(|last| last.elapsed_ms > elapsed_ms) {
+        samples.clear();
+    }
+
+    let speed = match samples.back() {
+        Some(last) if elapsed_ms > last.elapsed_ms => {
+            copied.saturating_sub(last.copied_bytes) * 1000 / (elapsed_ms - last.elapsed_ms)
+        },
+        Some(last) => last.speed,
+        None if elapsed_ms > 0 => copied * 1000 / elapsed_ms,
+        None => 0,
+    };
+
+    samples.push_back(SpeedSample {
+        elapsed_ms,
+        copied_bytes: copied,
+        total_bytes: total,
+        speed,
+    });
+    if samples.len() > MAX_SAMPLES {
+        samples.pop_front();
+    }
+}
+
+#[tauri::command]
+pub fn get_transfer_timeline(state: tauri::State<TransferTimelines>, folder: String) -> Vec<SpeedSample> {
+    state.folders.lock().unwrap()
+        .get(&folder)
+        .map(|samples| samples.iter().cloned().collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+use std::fs::FileType;
+use std::path::{Path, PathBuf};
+
+/// What a recursive walk should do with one directory entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkEntry {
+    Dir,
+    File,
+    Link,  // A symlink (or junction) left alone: follow_symlinks is off, or it points nowhere
+    Cycle, // A directory already walked, reached again through a link
+}
+
+/// Keeps recursive walks finite. With `follow_symlinks` off links are never entered; with it on
+/// they are, but every directory is remembered by its canonical path so a link back to an
+/// ancestor (or to a folder already walked) is reported instead of walked again.
+pub struct SymlinkGuard {
+    follow: bool,
+    visited: HashSet<PathBuf>,
+}
+
+impl SymlinkGuard {
+    pub fn new(root: &Path, follow: bool) -> Self {
+        let mut visited = HashSet::new();
+        if follow {
+            if let Ok(canonical) = root.canonicalize() {
+                visited.insert(canonical);
+            }
+        }
+        Self { follow, visited }
+    }
+
+    pub fn classify(&mut self, path: &Path, file_type: FileType) -> WalkEntry {
+        if file_type.is_symlink() && !self.follow {
+            return WalkEntry::Link;
+        }
+        if file_type.is_file() {
+            return WalkEntry::File;
+        }
+        // A followed link takes the type of its target
+        let is_dir = if file_type.is_symlink() {
+            match std::fs::metadata(path) {
+                Ok(target) => target.is_dir(),
+                Err(_) => return WalkEntry::Link,
+            }
+        } else {
+            file_type.is_dir()
+        };
+        if !is_dir {
+            return WalkEntry::File;
+        }
+        if self.follow {
+            let Ok(canonical) = path.canonicalize() else { return WalkEntry::Link };
+            if !self.visited.insert(canonical) {
+                return WalkEntry::Cycle;
+            }
+        }
+        WalkEntry::Dir
+    }
+}
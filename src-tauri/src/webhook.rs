@@ -0,0 +1,26 @@
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::config::AppConfig;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// POSTs `payload` as JSON to `webhook_url` if the success/failure toggle allows it.
+/// Blocking; failures are only logged so a dead endpoint never fails a scan or deploy.
+pub fn notify(config: &AppConfig, success: bool, payload: Value) {
+    let Some(url) = config.webhook_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) else {
+        return;
+    };
+    let wanted = if success { config.webhook_on_success } else { config.webhook_on_failure };
+    if !wanted {
+        return;
+    }
+
+    let result = ureq::post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .set("Content-Type", "application/json")
+        .send_string(&payload.to_string());
+    if let Err(e) = result {
+        log::warn!("Failed to reach webhook {}: {}", url, e);
+    }
+}
@@ -8,7 +8,13 @@ export interface DeployServer {
   port: number;
   user: string;
   password: string;
+  auth_method: 'Password' | 'PublicKey';
+  private_key_path: string | null;
+  passphrase: string | null; // Only for encrypted keys
   remote_path: string;
+  staging_path: string | null; // Upload here first, then move into remote_path
+  remote_folder_name_template: string; // Empty keeps the local folder name
+  deploy_single_artifact: string | null; // e.g. 'tar.gz' to only upload the newest archive
 }
 
 export interface MatchRule {
@@ -25,21 +31,63 @@ export interface ScanTask {
   rule: MatchRule;
 }
 
+export type LogLevel = 'trace' | 'debug' | 'info' | 'success' | 'warn' | 'error';
+
 export interface AppConfig {
   tasks: ScanTask[];
   
   // Legacy (kept for type compatibility if needed, but UI should focus on tasks)
   remote_paths: string[];
   target_versions: string[];
+  path_versions: Record<string, string[]>;
   
   local_path: string;
+  additional_local_paths: string[]; // Extra copies of whatever goes to local_path
+  create_local_path_if_missing: boolean;
   interval_minutes: number;
+  scheduler_enabled: boolean; // Backend scheduler, independent of the Start button
   time_ranges: string[]; // Format "HH:mm-HH:mm" e.g. "05:00-09:00"
   file_extensions: string[];
   filename_includes: string[];
+  skip_hidden_files: boolean; // Dotfiles, hidden/system files, Thumbs.db, desktop.ini
+  exclude_patterns: string[]; // Globs relative to the build folder, e.g. '*.tmp', 'node_modules/**'
+  pinned_folders: string[];
+  max_age_days: number; // Today plus this many days back; 0 = today only
+  versions_to_keep: number; // Newest builds per version copied each scan
+  folder_patterns: string[];
+  datetime_format: string; // chrono format for the datetime group, empty = '%Y_%m_%d_%H_%M'
+  default_version: string; // Used when a folder name has no version part
+  empty_versions_behavior: string; // 'nothing' | 'newest_any'
+  tie_breaker: string; // 'mtime' | 'name'
+  copy_order: string; // 'walk' | 'name' | 'size_asc' | 'size_desc'
+  force_overwrite_readonly: boolean;
+  max_bytes_per_sec: number;
+  max_concurrency: number;
+  normalize_line_endings?: string | null; // 'lf' | 'crlf'
+  line_ending_extensions: string[];
+  use_reflink: boolean;
+  detect_source_drift: boolean;
+  completion_sentinel?: string | null; // e.g. 'DONE'
+  move_instead_of_copy: boolean; // Consumes the source files
+  sync_mode: 'SkipExisting' | 'Incremental' | 'Overwrite'; // For folders already at the destination
+  follow_symlinks: boolean; // Off: symlinks are skipped during copy and upload
+  preserve_ownership: boolean;
+  post_copy_delay_ms: number;
+  per_file_timeout_secs: number; // 0 = no watchdog
+  fsync_before_deploy: boolean;
+  staging_max_age_hours: number;
+  safe_mode: boolean; // Overrides deploy and all delete/remote-command switches
+  quiet_mode: boolean;
+  redact_paths: boolean;
+  history_limit: number; // Entries kept, 0 = unlimited
+  history_from_log_levels: LogLevel[]; // Log levels also recorded in history, e.g. ['error', 'warn']
+  min_log_level: LogLevel; // Lower lines reach neither the console nor app.log
   
   deploy_enabled: boolean;
   servers: DeployServer[];
+  respect_folder_descriptor: boolean; // Honor .deploy.json inside the copied folder
+  max_connections_per_host: number;
+  connect_timeout_secs: number;
   
   // Legacy
   ssh_host: string;
@@ -49,31 +97,140 @@ export interface AppConfig {
   remote_linux_path: string;
   
   post_commands: string[];
+  post_copy_commands: string[]; // Local commands after each copy, ${target} = copied folder
+  post_copy_command_timeout_secs: number; // 0 = no limit
+  max_command_output_bytes: number; // 0 = unlimited
+  command_output_flush_ms: number; // Post-command output is logged in batches at most this often
+  filename_search_depth: number;
+  filename_pick: string; // 'name' | 'newest'
+  tar_upload: boolean;
+  upload_retries: number; // Reconnect-and-resume attempts after a failed upload
+  verify_upload: boolean;
+  remote_hash_command: string;
+  backup_before_deploy: boolean;
+  mirror_delete: boolean; // Deletes remote files missing locally after each upload
+  rollback_commands: string[];
+  remote_precheck_paths: string[];
+  remote_precheck_remove: boolean;
+  webhook_url?: string | null;
+  webhook_on_success: boolean;
+  webhook_on_failure: boolean;
 }
 
 export interface ScanResult {
   scanned_paths: number;
   found_folders: string[];
   copied_folders: string[];
+  failed_folders: string[]; // Copy failed, or copied but the deploy failed
   errors: string[];
+  phase_durations: PhaseDurations;
+  run_id: string;
+  bytes_copied: number;
+  duration_ms: number;
+}
+
+// Payload of the 'scan-complete' event
+export interface ScanCompleteEvent extends ScanResult {
+  skipped_folders: number;
+  failed_count: number;
+  clean: boolean;
+}
+
+export interface PhaseDurations {
+  enumeration_ms: number;
+  filtering_ms: number;
+  copy_ms: number;
+  deploy_ms: number;
 }
 
 export async function getConfig(): Promise<AppConfig> {
   return await invoke('get_config');
 }
 
-export async function saveConfig(config: AppConfig): Promise<void> {
-  await invoke('save_config_cmd', { config });
+export async function getEffectiveConfig(): Promise<AppConfig> {
+  return await invoke('get_effective_config');
+}
+
+export async function getConfigError(): Promise<string | null> {
+  return await invoke('get_config_error');
+}
+
+// Problems with a config that did load, e.g. a password that must be entered again
+export async function getConfigWarnings(): Promise<string[]> {
+  return await invoke('get_config_warnings');
+}
+
+export interface NormalizedExtensions {
+  extensions: string[];
+  warnings: string[];
+}
+
+export async function normalizeExtensions(exts: string[]): Promise<NormalizedExtensions> {
+  return await invoke('normalize_extensions', { exts });
+}
+
+// Overlapping, empty or unparseable HH:mm-HH:mm ranges
+export async function validateTimeRanges(ranges: string[]): Promise<string[]> {
+  return await invoke('validate_time_ranges', { ranges });
+}
+
+export interface PatternTest {
+  matched: boolean;
+  datetime_text: string | null;
+  datetime: string | null; // "YYYY-MM-DD HH:MM"
+  datetime_error: string | null;
+  version: string | null;
+}
+
+export async function testPattern(pattern: string, datetimeFormat: string, sampleName: string): Promise<PatternTest> {
+  return await invoke('test_pattern', { pattern, datetimeFormat, sampleName });
+}
+
+// Resolves to warnings that didn't block the save (e.g. overlapping time ranges)
+export async function saveConfig(config: AppConfig): Promise<string[]> {
+  return await invoke('save_config_cmd', { config });
+}
+
+export async function exportConfig(path: string, includeSecrets: boolean): Promise<void> {
+  return await invoke('export_config', { path, includeSecrets });
+}
+
+// Returns the same warnings as saveConfig; reload the config afterwards
+export async function importConfig(path: string): Promise<string[]> {
+  return await invoke('import_config', { path });
 }
 
 export async function scanNow(): Promise<ScanResult> {
   return await invoke('scan_now');
 }
 
+export interface TreeEntry {
+  name: string;
+  is_dir: boolean;
+  version: string | null; // null when the name fits no folder pattern
+  datetime: string | null;
+  modified: string | null;
+  matched: boolean; // An enabled task on this path would pick this version
+}
+
+export interface DirectoryTree {
+  entries: TreeEntry[];
+  total: number;
+  truncated: boolean;
+}
+
+export async function getDirectoryTree(remotePath: string, maxEntries: number): Promise<DirectoryTree> {
+  return await invoke('get_directory_tree', { remotePath, maxEntries });
+}
+
 export async function cancelScan(): Promise<void> {
   await invoke('cancel_scan');
 }
 
+export async function cancelScanAndWait(timeoutSecs: number): Promise<boolean> {
+  return await invoke('cancel_scan_and_wait', { timeoutSecs });
+}
+
 export async function pauseScan(): Promise<void> {
   await invoke('pause_scan');
 }
@@ -82,6 +239,19 @@ export async function resumeScan(): Promise<void> {
   await invoke('resume_scan');
 }
 
+// Act on manual deploys and the deploy step of a running scan, independently of the scan controls
+export async function cancelDeploy(): Promise<void> {
+  await invoke('cancel_deploy');
+}
+
+export async function pauseDeploy(): Promise<void> {
+  await invoke('pause_deploy');
+}
+
+export async function resumeDeploy(): Promise<void> {
+  await invoke('resume_deploy');
+}
+
 export async function addSystemEvent(action: string, desc: string): Promise<void> {
   await invoke('add_system_event', { action, desc });
 }
@@ -97,28 +267,189 @@ export interface HistoryEntry {
   copied_files_count: number;
   total_size: number;
   files: string[];
+  run_id: string;
+  command_results?: CommandResult[]; // Deploy entries only
 }
 
-export interface HistoryStore {
+// One page, newest first; `total` counts every entry kept
+export interface HistoryPage {
   entries: HistoryEntry[];
+  total: number;
 }
 
-export async function getHistory(): Promise<HistoryStore> {
-  return await invoke('get_history');
+export async function getHistory(offset?: number, limit?: number): Promise<HistoryPage> {
+  return await invoke('get_history', { offset, limit });
 }
 
 export async function clearHistory(): Promise<void> {
   await invoke('clear_history');
 }
 
+export interface SpeedSample {
+  elapsed_ms: number;
+  copied_bytes: number;
+  total_bytes: number;
+  speed: number;
+}
+
+export async function getTransferTimeline(folder: string): Promise<SpeedSample[]> {
+  return await invoke('get_transfer_timeline', { folder });
+}
+
+export interface LogEntry {
+  timestamp: string;
+  level: LogLevel;
+  category: string; // 'scan' | 'copy' | 'deploy' | 'system'
+  run_id: string | null;
+  msg: string;
+}
+
+// level is a minimum severity: 'warn' also returns errors
+export async function getRecentLogs(level?: LogLevel, category?: string, runId?: string): Promise<LogEntry[]> {
+  return await invoke('get_recent_logs', { level, category, runId });
+}
+
 export async function testSshConnection(server: DeployServer): Promise<string> {
   return await invoke('test_ssh_connection', { server });
 }
 
+// Also checks that remote_path is writable and reports its free space
+export async function testRemotePath(server: DeployServer): Promise<string> {
+  return await invoke('test_remote_path', { server });
+}
+
+export interface ServerProbe {
+  banner: string;
+  host_key_type: string;
+  host_key_fingerprint: string;
+  authenticated: boolean;
+  auth_error: string | null;
+  sftp_ok: boolean;
+  sftp_error: string | null;
+}
+
+export async function probeServer(server: DeployServer): Promise<ServerProbe> {
+  return await invoke('probe_server', { server });
+}
+
+export interface RemoteEntry {
+  name: string;
+  is_dir: boolean;
+  size: number;
+  mtime: number | null; // Unix seconds
+}
+
+export async function listRemoteDir(server: DeployServer, path: string): Promise<RemoteEntry[]> {
+  return await invoke('list_remote_dir', { server, path });
+}
+
 export async function manualDeploy(server: DeployServer, postCommands: string[], localPath: string, remotePath: string): Promise<void> {
   await invoke('manual_deploy', { server, postCommands, localPath, remotePath });
 }
 
+// Re-deploys a folder after an interrupted deploy, skipping files that already made it
+export async function deployResume(server: DeployServer, localPath: string): Promise<void> {
+  await invoke('deploy_resume', { server, localPath });
+}
+
+export interface RetryOutcome {
+  succeeded: string[]; // Server names
+  failed: string[];
+}
+
+// Scans once after the delay; the `scheduled-scan` event reports whether it ran or was skipped
+export async function scheduleOneShotScan(delaySecs: number): Promise<string> {
+  return await invoke('schedule_one_shot_scan', { delaySecs });
+}
+
+export async function cancelScheduledScan(jobId: string): Promise<void> {
+  await invoke('cancel_scheduled_scan', { jobId });
+}
+
+// Skips one server for the deploy batch in progress; its saved `enabled` flag is unchanged
+export async function disableServerForRun(serverId: string): Promise<void> {
+  await invoke('disable_server_for_run', { serverId });
+}
+
+// Re-deploys a folder only to the servers whose last deploy of it failed
+export async function retryFailedServers(localPath: string, folderName: string): Promise<RetryOutcome> {
+  return await invoke('retry_failed_servers', { localPath, folderName });
+}
+
+export async function rollbackDeploy(server: DeployServer, remotePath: string): Promise<void> {
+  await invoke('rollback_deploy', { server, remotePath });
+}
+
+export async function setRuntimeLimits(bandwidth: number, concurrency: number): Promise<void> {
+  await invoke('set_runtime_limits', { bandwidth, concurrency });
+}
+
+export interface CommandResult {
+  command: string;
+  output: string;
+  exit_code: number;
+}
+
+export async function rerunPostCommands(server: DeployServer, folderName: string, localPath: string): Promise<CommandResult[]> {
+  return await invoke('rerun_post_commands', { server, folderName, localPath });
+}
+
+export async function emergencyStop(timeoutSecs?: number): Promise<string[]> {
+  return await invoke('emergency_stop', { timeoutSecs });
+}
+
+export async function cleanupStaging(): Promise<string[]> {
+  return await invoke('cleanup_staging');
+}
+
+export async function exportDiagnostics(path: string): Promise<void> {
+  await invoke('export_diagnostics', { path });
+}
+
+export interface SelfTestStage {
+  name: string;
+  ok: boolean;
+  detail: string;
+}
+
+export async function selfTest(): Promise<SelfTestStage[]> {
+  return await invoke('self_test');
+}
+
+export interface SourceBenchmark {
+  files_read: number;
+  bytes_read: number;
+  duration_ms: number;
+  throughput_bytes_per_sec: number;
+  avg_open_latency_ms: number;
+  max_open_latency_ms: number;
+}
+
+export async function benchmarkSource(remotePath: string): Promise<SourceBenchmark> {
+  return await invoke('benchmark_source', { remotePath });
+}
+
+export async function getLastSuccessfulScan(): Promise<string | null> {
+  return await invoke('get_last_successful_scan');
+}
+
 export async function getAppPaths(): Promise<[string, string]> {
   return await invoke('get_app_paths');
 }
+
+export interface RuntimeStats {
+  total_scans: number;
+  total_folders_copied: number;
+  total_bytes_copied: number;
+  total_deploys: number;
+  total_errors: number;
+  since: string; // When counting started or was last reset
+}
+
+export async function getRuntimeStats(): Promise<RuntimeStats> {
+  return await invoke('get_runtime_stats');
+}
+
+export async function resetRuntimeStats(): Promise<RuntimeStats> {
+  return await invoke('reset_runtime_stats');
+}
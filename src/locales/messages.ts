@@ -42,12 +42,18 @@ export const messages = {
       eta: 'ETA',
       elapsed: 'Elapsed',
       schedulerControls: 'Scheduler Controls',
+      neverSuccessfulScan: 'No scan has copied anything yet.',
+      staleSuccessfulScan: 'No successful scan in the last 24 hours (last: {time}). Check the network shares and settings.',
     },
     settings: {
       title: 'Settings',
       save: 'Save Changes',
       saved: 'Settings saved successfully!',
+      savedWithWarnings: 'Settings saved, but check: {details}',
       saveError: 'Error saving: {error}',
+      invalidExtensions: 'Not saved, invalid extensions: {details}',
+      configCorrupt: 'Your saved settings could not be loaded, defaults are in use. Saving will overwrite them. {error}',
+      configWarnings: 'Settings loaded with problems: {warnings}',
       localStorage: 'Local Storage',
       localPath: 'Local Target Directory',
       localPathDesc: 'Files will be copied to this directory',
@@ -75,8 +81,16 @@ export const messages = {
       port: 'Port',
       username: 'Username',
       password: 'Password',
+      authMethod: 'Authentication',
+      privateKey: 'Private Key',
+      privateKeyPath: 'Private Key File',
+      passphrase: 'Key Passphrase',
       remoteTargetDir: 'Remote Target Directory',
       remoteTargetDirPlaceholder: '/home/user/deploy',
+      stagingPath: 'Staging Directory',
+      stagingPathPlaceholder: 'Optional, e.g. /opt/staging (moved into the target when complete)',
+      remoteFolderName: 'Remote Folder Name',
+      remoteFolderNamePlaceholder: "Empty = local name, e.g. current or {'$'}{'{'}version{'}'}",
       postCommands: 'Post-Copy Commands',
       commandPlaceholder: 'e.g. tar -zxvf file.tar.gz',
       noCommands: 'No commands configured',
@@ -132,6 +146,9 @@ export const messages = {
       size: 'Size',
       count: 'Count',
       viewFiles: 'View Files',
+      commands: 'Post Commands',
+      exitCode: 'exit',
+      loadMore: 'Load more ({count} remaining)',
     },
   },
   zh: {
@@ -177,12 +194,18 @@ export const messages = {
       eta: '估计剩余时间',
       elapsed: '经过时间',
       schedulerControls: '定时任务控制',
+      neverSuccessfulScan: '尚未有扫描成功复制过文件。',
+      staleSuccessfulScan: '最近 24 小时内没有成功的扫描（上次: {time}），请检查网络共享和设置。',
     },
     settings: {
       title: '设置',
       save: '保存更改',
       saved: '设置保存成功！',
+      savedWithWarnings: '设置已保存，但请检查: {details}',
       saveError: '保存失败: {error}',
+      invalidExtensions: '未保存，扩展名无效: {details}',
+      configCorrupt: '无法加载已保存的配置，当前使用默认设置，保存将覆盖原配置。{error}',
+      configWarnings: '配置已加载，但存在问题：{warnings}',
       localStorage: '本地存储',
       localPath: '本地目标目录',
       localPathDesc: '文件将被拷贝到此目录',
@@ -210,8 +233,16 @@ export const messages = {
       port: '端口',
       username: '用户名',
       password: '密码',
+      authMethod: '认证方式',
+      privateKey: '私钥',
+      privateKeyPath: '私钥文件',
+      passphrase: '私钥密码',
       remoteTargetDir: '远程目标目录',
       remoteTargetDirPlaceholder: '/home/user/deploy',
+      stagingPath: '暂存目录',
+      stagingPathPlaceholder: '可选，如 /opt/staging（上传完成后移动到目标目录）',
+      remoteFolderName: '远程目录名',
+      remoteFolderNamePlaceholder: "留空使用本地目录名，如 current 或 {'$'}{'{'}version{'}'}",
       postCommands: '后置执行命令',
       commandPlaceholder: '例如 tar -zxvf file.tar.gz',
       noCommands: '未配置命令',
@@ -267,6 +298,9 @@ export const messages = {
       size: '大小',
       count: '数量',
       viewFiles: '查看文件',
+      commands: '部署后命令',
+      exitCode: '退出码',
+      loadMore: '加载更多（剩余 {count} 条）',
     },
   },
 };